@@ -0,0 +1,331 @@
+use crate::bar::Bar;
+use crate::unit::Unit;
+#[cfg(feature = "futures")]
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+#[cfg(feature = "futures")]
+use pin_project_lite::pin_project;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+#[cfg(feature = "futures")]
+use std::pin::Pin;
+#[cfg(feature = "futures")]
+use std::task::{Context, Poll};
+
+/// Defines an adapter with an inner reader or writer and a progress bar, the inner one is pinned with the `futures` feature
+macro_rules! adapter {
+    ($(#[$attr:meta])* pub struct $name:ident<$inner:ident>) => {
+        #[cfg(feature = "futures")]
+        pin_project! {
+            $(#[$attr])*
+            pub struct $name<$inner> {
+                #[pin]
+                inner: $inner,
+                bar: Bar,
+            }
+        }
+
+        #[cfg(not(feature = "futures"))]
+        $(#[$attr])*
+        pub struct $name<$inner> {
+            inner: $inner,
+            bar: Bar,
+        }
+    };
+}
+
+adapter! {
+    /// A reader, that advances a progress bar by the number of bytes read from it.
+    ///
+    /// The progress bar is [finished](struct.Bar.html#method.finish), once the end of the inner reader is reached.
+    /// If the inner reader implements `BufRead`, so does the ProgressReader.
+    /// With the `futures` feature, the same holds for `AsyncRead` and `AsyncBufRead`.
+    /// # Example
+    /// ```
+    /// use prgrs::ProgressReader;
+    /// use std::io::Read;
+    /// let data = vec![0u8; 4096];
+    /// let mut reader = ProgressReader::new(&data[..], data.len() as u64);
+    /// let mut copy = Vec::new();
+    /// reader.read_to_end(&mut copy).unwrap();
+    /// ```
+    pub struct ProgressReader<R>
+}
+
+impl<R> ProgressReader<R> {
+    /// Wraps the reader in a progress bar with the given total in bytes
+    pub fn new(inner: R, total: u64) -> Self {
+        Self::with_bar(inner, Bar::new(total).set_unit_move(Unit::BinaryBytes))
+    }
+
+    /// Wraps the reader in the given progress bar, which can be configured beforehand
+    /// # Example
+    /// ```
+    /// use prgrs::{Bar, ProgressReader, Unit};
+    /// let data = vec![0u8; 4096];
+    /// let bar = Bar::with_unknown_total()
+    ///     .set_unit_move(Unit::Bytes)
+    ///     .set_desc_move("reading");
+    /// let reader = ProgressReader::with_bar(&data[..], bar);
+    /// ```
+    pub fn with_bar(inner: R, bar: Bar) -> Self {
+        ProgressReader { inner, bar }
+    }
+
+    /// Returns a reference to the inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader, reading from it directly doesn't advance the progress bar
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the progress bar, to change its settings while reading
+    pub fn bar_mut(&mut self) -> &mut Bar {
+        &mut self.bar
+    }
+
+    /// Finishes the progress bar and returns the inner reader
+    pub fn into_inner(mut self) -> R {
+        self.bar.finish();
+        self.inner
+    }
+}
+
+impl ProgressReader<File> {
+    /// Wraps the file in a progress bar, whose total is the size of the file
+    /// # Example
+    /// ```no_run
+    /// use prgrs::ProgressReader;
+    /// use std::fs::File;
+    /// use std::io;
+    /// let mut reader = ProgressReader::from_file(File::open("data.bin")?)?;
+    /// io::copy(&mut reader, &mut io::sink())?;
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn from_file(file: File) -> io::Result<Self> {
+        let total = file.metadata()?.len();
+        Ok(Self::new(file, total))
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.bar.finish();
+        } else {
+            self.bar.inc(n as u64);
+        }
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for ProgressReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buf = self.inner.fill_buf()?;
+        if buf.is_empty() {
+            self.bar.finish();
+        }
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.bar.inc(amt as u64);
+    }
+}
+
+adapter! {
+    /// A writer, that advances a progress bar by the number of bytes written to it.
+    ///
+    /// Since the writer can't know, when everything is written, call [into_inner()](struct.ProgressWriter.html#method.into_inner) to finish the progress bar.
+    /// With the `futures` feature, it also implements `AsyncWrite`, if the inner writer does, and closing it finishes the progress bar.
+    /// # Example
+    /// ```
+    /// use prgrs::ProgressWriter;
+    /// use std::io::Write;
+    /// let data = vec![0u8; 4096];
+    /// let mut writer = ProgressWriter::new(Vec::new(), data.len() as u64);
+    /// for chunk in data.chunks(1024) {
+    ///     writer.write_all(chunk).unwrap();
+    /// }
+    /// let copy = writer.into_inner();
+    /// ```
+    pub struct ProgressWriter<W>
+}
+
+impl<W> ProgressWriter<W> {
+    /// Wraps the writer in a progress bar with the given total in bytes
+    pub fn new(inner: W, total: u64) -> Self {
+        Self::with_bar(inner, Bar::new(total).set_unit_move(Unit::BinaryBytes))
+    }
+
+    /// Wraps the writer in the given progress bar, which can be configured beforehand
+    pub fn with_bar(inner: W, bar: Bar) -> Self {
+        ProgressWriter { inner, bar }
+    }
+
+    /// Returns a reference to the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer, writing to it directly doesn't advance the progress bar
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the progress bar, to change its settings while writing
+    pub fn bar_mut(&mut self) -> &mut Bar {
+        &mut self.bar
+    }
+
+    /// Finishes the progress bar and returns the inner writer
+    pub fn into_inner(mut self) -> W {
+        self.bar.finish();
+        self.inner
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bar.inc(n as u64);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R: AsyncRead> AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        match poll {
+            Poll::Ready(Ok(0)) if !buf.is_empty() => this.bar.finish(),
+            Poll::Ready(Ok(n)) => this.bar.inc(n as u64),
+            _ => {}
+        }
+        poll
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R: AsyncBufRead> AsyncBufRead for ProgressReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.project();
+        let poll = this.inner.poll_fill_buf(cx);
+        if let Poll::Ready(Ok(&[])) = poll {
+            this.bar.finish();
+        }
+        poll
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.project();
+        this.inner.consume(amt);
+        this.bar.inc(amt as u64);
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<W: AsyncWrite> AsyncWrite for ProgressWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.bar.inc(n as u64);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        if let Poll::Ready(Ok(())) = poll {
+            this.bar.finish();
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{plain, Buffer};
+
+    #[test]
+    fn test_reader() {
+        let buf = Buffer::default();
+        let data = [1u8, 2, 3, 4];
+        let mut reader = ProgressReader::with_bar(&data[..], plain(Bar::new(4), &buf));
+        let mut chunk = [0; 2];
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(reader.bar_mut().position(), 2);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [3, 4]);
+        assert!(buf.contents().ends_with("(100%)\n"));
+    }
+
+    #[test]
+    fn test_buf_reader() {
+        let buf = Buffer::default();
+        let mut reader = ProgressReader::with_bar(&b"a\nbc\n"[..], plain(Bar::new(5), &buf));
+        let lines: Vec<String> = reader.by_ref().lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["a", "bc"]);
+        assert_eq!(reader.bar_mut().position(), 5);
+        assert!(buf.contents().ends_with("(100%)\n"));
+    }
+
+    #[test]
+    fn test_writer() {
+        let buf = Buffer::default();
+        let mut writer = ProgressWriter::with_bar(Vec::new(), plain(Bar::new(4), &buf));
+        writer.write_all(&[1, 2, 3]).unwrap();
+        assert_eq!(writer.bar_mut().position(), 3);
+        assert!(!buf.contents().ends_with('\n'));
+        assert_eq!(writer.into_inner(), [1, 2, 3]);
+        assert!(buf.contents().ends_with("( 75%)\n"));
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_async() {
+        use futures::executor::block_on;
+        use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+        let buf = Buffer::default();
+        let mut reader =
+            ProgressReader::with_bar(Cursor::new([1u8, 2, 3, 4]), plain(Bar::new(4), &buf));
+        let mut data = Vec::new();
+        block_on(reader.read_to_end(&mut data)).unwrap();
+        assert_eq!(data, [1, 2, 3, 4]);
+        assert!(buf.contents().ends_with("(100%)\n"));
+        let buf = Buffer::default();
+        let mut writer =
+            ProgressWriter::with_bar(Cursor::new(Vec::new()), plain(Bar::new(4), &buf));
+        block_on(async {
+            writer.write_all(&data).await.unwrap();
+            writer.close().await.unwrap();
+        });
+        assert_eq!(writer.bar_mut().position(), 4);
+        assert!(buf.contents().ends_with("(100%)\n"));
+    }
+}
@@ -0,0 +1,1176 @@
+use crate::color::{self, Color, Colors};
+use crate::format;
+use crate::labels::Labels;
+use crate::multi::Slot;
+use crate::output::{NonTerminal, Output};
+use crate::screen;
+use crate::style::Style;
+use crate::template::{Key, Rendered, Template};
+use crate::unit::Unit;
+use crate::{Indicator, Length};
+use std::io::{Error, Write};
+use std::time::{Duration, Instant};
+
+/// A progress bar, that isn't tied to an Iterator.
+///
+/// Use it, when the progress is reported in some other way, like from a callback or while downloading something.
+/// It is drawn the same way as [Prgrs](struct.Prgrs.html) and has the same settings.
+///
+/// If a bar is dropped, before [finish()](struct.Bar.html#method.finish) was called, it is [abandoned](struct.Bar.html#method.abandon).
+/// # Example
+/// ```
+/// use prgrs::Bar;
+/// let mut bar = Bar::new(1000);
+/// for chunk in vec![100; 10] {
+///     // process the chunk here
+///     bar.inc(chunk);
+/// }
+/// bar.finish();
+/// ```
+pub struct Bar {
+    total: Option<u64>,
+    pos: u64,
+    len: Length,
+    output: Output,
+    start: Option<Instant>,
+    last_change: Option<Instant>,
+    min_interval: Duration,
+    min_iterations: u64,
+    last_draw: Option<(Instant, u64)>,
+    last_frame: String,
+    frames: u64,
+    rate: Option<f64>,
+    show_elapsed: bool,
+    show_eta: bool,
+    show_rate: bool,
+    unit: Unit,
+    indicator: Indicator,
+    style: Style,
+    smooth: bool,
+    colors: Colors,
+    state: State,
+    template: Option<Template>,
+    labels: Labels,
+    leave: bool,
+    slot: Option<Slot>,
+    non_terminal: NonTerminal,
+    last_log: Option<(Instant, u64)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Running,
+    Finished,
+    Abandoned,
+}
+
+/// The characters used for the partially filled cell of a smooth bar, from one eighth to seven eighths
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// The weight of the newest measurement in the moving average of the rate
+const SMOOTHING: f64 = 0.3;
+
+/// How often a line is printed to a non-terminal output, when a percentage is asked for, but the total is unknown
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+impl Bar {
+    /// Creates a new progress bar with the given total
+    pub fn new(total: u64) -> Self {
+        Self::with_total(Some(total))
+    }
+
+    /// Creates a new progress bar, whose total isn't known yet.
+    ///
+    /// Until it is set with [set_total()](struct.Bar.html#method.set_total), a block bouncing back and forth is shown instead of the progress.
+    pub fn with_unknown_total() -> Self {
+        Self::with_total(None)
+    }
+
+    pub(crate) fn with_total(total: Option<u64>) -> Self {
+        Bar {
+            total,
+            pos: 0,
+            len: Length::Proportional(0.33),
+            output: Output::default(),
+            start: None,
+            last_change: None,
+            min_interval: Duration::from_millis(100),
+            min_iterations: 1,
+            last_draw: None,
+            last_frame: String::new(),
+            frames: 0,
+            rate: None,
+            show_elapsed: true,
+            show_eta: true,
+            show_rate: true,
+            unit: Unit::default(),
+            indicator: Indicator::Percentage,
+            style: Style::default(),
+            smooth: false,
+            colors: Colors::default(),
+            state: State::Running,
+            template: None,
+            labels: Labels::default(),
+            leave: true,
+            slot: None,
+            non_terminal: NonTerminal::default(),
+            last_log: None,
+        }
+    }
+
+    /// Advances the progress bar by `n` and redraws it, if enough time has passed since the last redraw
+    /// # Example
+    /// ```
+    /// use prgrs::Bar;
+    /// let mut bar = Bar::new(100);
+    /// bar.inc(42);
+    /// assert_eq!(bar.position(), 42);
+    /// ```
+    pub fn inc(&mut self, n: u64) {
+        self.set_position(self.pos.saturating_add(n));
+    }
+
+    /// Sets the position of the progress bar and redraws it, if enough time has passed since the last redraw
+    /// # Example
+    /// ```
+    /// use prgrs::Bar;
+    /// let mut bar = Bar::new(100);
+    /// bar.set_position(42);
+    /// assert_eq!(bar.position(), 42);
+    /// ```
+    pub fn set_position(&mut self, pos: u64) {
+        self.step();
+        if pos != self.pos {
+            self.last_change = Some(Instant::now());
+        }
+        self.pos = pos;
+        self.update();
+    }
+
+    /// Redraws the progress bar without any progress, if enough time has passed since the last redraw.
+    ///
+    /// Call it regularly, while waiting for progress, so the progress bar keeps showing the elapsed time, the bouncing block keeps moving and a [stalled](struct.Colors.html#structfield.stalled) progress bar changes its color.
+    /// # Example
+    /// ```
+    /// use prgrs::Bar;
+    /// let mut bar = Bar::new(100);
+    /// for _ in 0..10 {
+    ///     // wait for something here
+    ///     bar.tick();
+    /// }
+    /// bar.inc(100);
+    /// bar.finish();
+    /// ```
+    pub fn tick(&mut self) {
+        if self.state != State::Running {
+            return;
+        }
+        self.step();
+        let due = match self.last_draw {
+            Some((time, _)) => time.elapsed() >= self.min_interval,
+            None => true,
+        };
+        if due {
+            self.draw();
+        }
+    }
+
+    /// Returns the current position of the progress bar
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Sets the total, which is useful when it wasn't known when the progress bar was created
+    /// # Example
+    /// ```
+    /// use prgrs::Bar;
+    /// let mut bar = Bar::with_unknown_total();
+    /// bar.inc(10);
+    /// bar.set_total(100);
+    /// assert_eq!(bar.total(), Some(100));
+    /// ```
+    pub fn set_total(&mut self, total: u64) {
+        self.total = Some(total);
+    }
+
+    /// Returns the total of the progress bar, if it is known
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Draws the progress bar a last time and moves to the next line, so the bar stays visible
+    ///
+    /// The position is left as it is, so a bar, that didn't reach its total, shows how far it got.
+    /// # Example
+    /// ```
+    /// use prgrs::Bar;
+    /// let mut bar = Bar::new(100);
+    /// bar.set_position(100);
+    /// bar.finish();
+    /// ```
+    pub fn finish(&mut self) {
+        self.end(State::Finished);
+    }
+
+    /// Same as [finish()](struct.Bar.html#method.finish), but the progress bar is marked as abandoned, which can be shown in a different [color](struct.Colors.html#structfield.abandoned)
+    /// # Example
+    /// ```
+    /// use prgrs::Bar;
+    /// let mut bar = Bar::new(100);
+    /// bar.set_position(42);
+    /// // something went wrong
+    /// bar.abandon();
+    /// ```
+    pub fn abandon(&mut self) {
+        self.end(State::Abandoned);
+    }
+
+    /// Set the length of the progress bar, see [Prgrs::set_length()](struct.Prgrs.html#method.set_length)
+    pub fn set_length(&mut self, len: Length) {
+        self.len = len;
+    }
+
+    /// Same as [set_length()](struct.Bar.html#method.set_length), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_length_move(mut self, len: Length) -> Self {
+        self.len = len;
+        self
+    }
+
+    /// Set the output the progress bar is drawn to, see [Prgrs::set_output()](struct.Prgrs.html#method.set_output)
+    pub fn set_output(&mut self, output: Output) {
+        self.output = output;
+    }
+
+    /// Same as [set_output()](struct.Bar.html#method.set_output), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_output_move(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Set the minimum time between two redraws, see [Prgrs::set_min_interval()](struct.Prgrs.html#method.set_min_interval)
+    pub fn set_min_interval(&mut self, min_interval: Duration) {
+        self.min_interval = min_interval;
+    }
+
+    /// Same as [set_min_interval()](struct.Bar.html#method.set_min_interval), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_min_interval_move(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Set how much the position has to advance between two redraws, see [Prgrs::set_min_iterations()](struct.Prgrs.html#method.set_min_iterations)
+    pub fn set_min_iterations(&mut self, min_iterations: u64) {
+        self.min_iterations = min_iterations;
+    }
+
+    /// Same as [set_min_iterations()](struct.Bar.html#method.set_min_iterations), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_min_iterations_move(mut self, min_iterations: u64) -> Self {
+        self.min_iterations = min_iterations;
+        self
+    }
+
+    /// Set the style of the progress bar, see [Prgrs::set_style()](struct.Prgrs.html#method.set_style)
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    /// Same as [set_style()](struct.Bar.html#method.set_style), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_style_move(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set whether the progress bar moves smoothly, see [Prgrs::set_smooth()](struct.Prgrs.html#method.set_smooth)
+    pub fn set_smooth(&mut self, smooth: bool) {
+        self.smooth = smooth;
+    }
+
+    /// Same as [set_smooth()](struct.Bar.html#method.set_smooth), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_smooth_move(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    /// Set the colors of the progress bar, see [Prgrs::set_colors()](struct.Prgrs.html#method.set_colors)
+    pub fn set_colors(&mut self, colors: Colors) {
+        self.colors = colors;
+    }
+
+    /// Same as [set_colors()](struct.Bar.html#method.set_colors), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_colors_move(mut self, colors: Colors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Set the template of the progress line, see [Prgrs::set_template()](struct.Prgrs.html#method.set_template)
+    pub fn set_template(&mut self, template: Template) {
+        self.template = Some(template);
+    }
+
+    /// Same as [set_template()](struct.Bar.html#method.set_template), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_template_move(mut self, template: Template) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Set the description shown in front of the progress bar, see [Prgrs::set_desc()](struct.Prgrs.html#method.set_desc)
+    pub fn set_desc(&mut self, desc: &str) {
+        self.labels.set_desc(desc);
+    }
+
+    /// Same as [set_desc()](struct.Bar.html#method.set_desc), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_desc_move(self, desc: &str) -> Self {
+        self.labels.set_desc(desc);
+        self
+    }
+
+    /// Set the postfix shown at the end of the progress line, see [Prgrs::set_postfix()](struct.Prgrs.html#method.set_postfix)
+    pub fn set_postfix(&mut self, postfix: &str) {
+        self.labels.set_postfix(postfix);
+    }
+
+    /// Same as [set_postfix()](struct.Bar.html#method.set_postfix), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_postfix_move(self, postfix: &str) -> Self {
+        self.labels.set_postfix(postfix);
+        self
+    }
+
+    /// Returns a [handle](struct.Labels.html) to change the description and the postfix, see [Prgrs::labels()](struct.Prgrs.html#method.labels)
+    pub fn labels(&self) -> Labels {
+        self.labels.clone()
+    }
+
+    /// Set the indicator, that shows how far the progress bar is, see [Prgrs::set_indicator()](struct.Prgrs.html#method.set_indicator)
+    pub fn set_indicator(&mut self, indicator: Indicator) {
+        self.indicator = indicator;
+    }
+
+    /// Same as [set_indicator()](struct.Bar.html#method.set_indicator), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_indicator_move(mut self, indicator: Indicator) -> Self {
+        self.indicator = indicator;
+        self
+    }
+
+    /// Set whether the elapsed time is shown, see [Prgrs::set_show_elapsed()](struct.Prgrs.html#method.set_show_elapsed)
+    pub fn set_show_elapsed(&mut self, show: bool) {
+        self.show_elapsed = show;
+    }
+
+    /// Same as [set_show_elapsed()](struct.Bar.html#method.set_show_elapsed), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_show_elapsed_move(mut self, show: bool) -> Self {
+        self.show_elapsed = show;
+        self
+    }
+
+    /// Set whether the estimated remaining time is shown, see [Prgrs::set_show_eta()](struct.Prgrs.html#method.set_show_eta)
+    pub fn set_show_eta(&mut self, show: bool) {
+        self.show_eta = show;
+    }
+
+    /// Same as [set_show_eta()](struct.Bar.html#method.set_show_eta), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_show_eta_move(mut self, show: bool) -> Self {
+        self.show_eta = show;
+        self
+    }
+
+    /// Set whether the rate is shown, see [Prgrs::set_show_rate()](struct.Prgrs.html#method.set_show_rate)
+    pub fn set_show_rate(&mut self, show: bool) {
+        self.show_rate = show;
+    }
+
+    /// Same as [set_show_rate()](struct.Bar.html#method.set_show_rate), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_show_rate_move(mut self, show: bool) -> Self {
+        self.show_rate = show;
+        self
+    }
+
+    /// Set whether the progress bar stays visible once it has ended, see [Prgrs::set_leave()](struct.Prgrs.html#method.set_leave)
+    pub fn set_leave(&mut self, leave: bool) {
+        self.leave = leave;
+    }
+
+    /// Same as [set_leave()](struct.Bar.html#method.set_leave), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_leave_move(mut self, leave: bool) -> Self {
+        self.leave = leave;
+        self
+    }
+
+    /// Set how the progress is shown, when the output isn't a terminal, see [Prgrs::set_non_terminal()](struct.Prgrs.html#method.set_non_terminal)
+    pub fn set_non_terminal(&mut self, non_terminal: NonTerminal) {
+        self.non_terminal = non_terminal;
+    }
+
+    /// Same as [set_non_terminal()](struct.Bar.html#method.set_non_terminal), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_non_terminal_move(mut self, non_terminal: NonTerminal) -> Self {
+        self.non_terminal = non_terminal;
+        self
+    }
+
+    /// Set the unit of the counter and the rate, see [Prgrs::set_unit()](struct.Prgrs.html#method.set_unit)
+    pub fn set_unit(&mut self, unit: Unit) {
+        self.unit = unit;
+    }
+
+    /// Same as [set_unit()](struct.Bar.html#method.set_unit), but the Bar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_unit_move(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Use this method to write to the output of the progress bar, while displaying it, see [Prgrs::writeln()](struct.Prgrs.html#method.writeln)
+    pub fn writeln(&mut self, text: &str) -> Result<(), Error> {
+        if self.is_logging() {
+            // There is no progress bar, that could be overwritten
+            return self.write_log(text);
+        }
+        if let Some(slot) = &self.slot {
+            return slot.writeln(text);
+        }
+        if self.state != State::Running {
+            // The ended progress bar already moved to the next line and isn't drawn again
+            return self.write_log(text);
+        }
+        // The progress bar is cleared first, so neither the length nor the number of lines of the text matter
+        let clear = " ".repeat(format::visible_len(&self.last_frame));
+        screen::update(&mut self.output, &[], |output| {
+            write!(output, "\r{}\r{}\n", clear, text)?;
+            output.flush()
+        })?;
+        self.last_frame.clear();
+        // A progress bar, that didn't start yet, would be left behind without a newline, since only started ones are ended when dropped
+        if self.start.is_some() {
+            self.draw();
+        }
+        Ok(())
+    }
+
+    /// Starts the timer on the first step
+    pub(crate) fn step(&mut self) {
+        if self.start.is_none() {
+            let now = Instant::now();
+            self.start = Some(now);
+            self.last_change = Some(now);
+        }
+    }
+
+    /// Redraws the progress bar, if enough time has passed since the last redraw
+    pub(crate) fn update(&mut self) {
+        if self.should_draw() {
+            self.draw();
+        }
+    }
+
+    /// Advances the position without drawing
+    pub(crate) fn advance(&mut self, n: u64) {
+        if n > 0 {
+            self.last_change = Some(Instant::now());
+        }
+        self.pos = self.pos.saturating_add(n);
+    }
+
+    /// Draws the progress bar in a line of a MultiBar instead of its own output
+    pub(crate) fn set_slot(&mut self, slot: Slot) {
+        self.slot = Some(slot);
+    }
+
+    /// Calls the function with the output the progress bar is drawn to
+    fn with_output<R, F: FnOnce(&Output) -> R>(&self, f: F) -> R {
+        match &self.slot {
+            Some(slot) => slot.with_output(f),
+            None => f(&self.output),
+        }
+    }
+
+    fn end(&mut self, state: State) {
+        if self.state != State::Running {
+            return;
+        }
+        self.state = state;
+        if self.is_logging() {
+            // The last line isn't drawn, so the rate would still be the one of the last redraw
+            self.update_rate(Instant::now());
+            self.log(true);
+            return;
+        }
+        if self.leave {
+            self.draw();
+        }
+        match &self.slot {
+            Some(slot) => slot.end(self.leave),
+            None if self.leave => {
+                screen::update(&mut self.output, &[], |output| writeln!(output).ok());
+            }
+            None => {
+                // Overwrite the last frame with whitespaces, so the line can be used again
+                let clear = " ".repeat(format::visible_len(&self.last_frame));
+                screen::update(&mut self.output, &[], |output| {
+                    write!(output, "\r{}\r", clear).ok();
+                    output.flush().ok();
+                });
+            }
+        }
+    }
+
+    /// Returns whether plain lines are printed instead of drawing the progress bar
+    fn is_logging(&self) -> bool {
+        self.non_terminal != NonTerminal::Draw && !self.with_output(Output::is_terminal)
+    }
+
+    /// Prints a plain line with the progress, if it is due, or if it is the last one and the progress changed since the last line
+    fn log(&mut self, last: bool) {
+        let due = match (self.non_terminal, self.last_log) {
+            (NonTerminal::Silent, _) | (NonTerminal::Draw, _) => false,
+            (_, None) => true,
+            (_, Some((_, pos))) if last => pos != self.pos,
+            (NonTerminal::Percentage(step), Some((time, pos))) => match self.total {
+                Some(total) if step > 0. => {
+                    let steps = |pos: u64| (pos as f64 / total as f64 * 100. / step).floor();
+                    steps(self.pos) > steps(pos)
+                }
+                _ => time.elapsed() >= LOG_INTERVAL,
+            },
+            (NonTerminal::Interval(interval), Some((time, _))) => time.elapsed() >= interval,
+        };
+        if due {
+            self.last_log = Some((Instant::now(), self.pos));
+            let line = self.create_log_line();
+            self.write_log(&line).ok();
+        }
+    }
+
+    fn write_log(&mut self, line: &str) -> Result<(), Error> {
+        match &self.slot {
+            Some(slot) => slot.write_log(line),
+            None => screen::update(&mut self.output, &[], |output| {
+                writeln!(output, "{}", line)?;
+                output.flush()
+            }),
+        }
+    }
+
+    /// Creates a line like `42% 420/1000 elapsed 00:04 eta 00:05 98.76it/s`
+    fn create_log_line(&self) -> String {
+        let texts = self.labels.get();
+        let mut parts = Vec::new();
+        if !texts.desc.is_empty() {
+            parts.push(format!("{}:", texts.desc));
+        }
+        match (self.get_percentage(), self.total) {
+            (Some(percentage), Some(total)) => {
+                parts.push(format!("{:.0}%", percentage));
+                parts.push(format!(
+                    "{}/{}",
+                    self.unit.amount(self.pos),
+                    self.unit.amount(total)
+                ));
+            }
+            _ => parts.push(self.unit.count(self.pos)),
+        }
+        if self.show_elapsed {
+            let elapsed = self.start.map(|s| s.elapsed()).unwrap_or_default();
+            parts.push(format!("elapsed {}", format::duration(elapsed)));
+        }
+        if self.show_eta && self.total.is_some() {
+            let eta = self
+                .get_eta()
+                .map_or_else(|| String::from("?"), format::duration);
+            parts.push(format!("eta {}", eta));
+        }
+        if self.show_rate {
+            parts.push(self.unit.rate(self.rate));
+        }
+        if !texts.postfix.is_empty() {
+            parts.push(texts.postfix);
+        }
+        parts.join(" ")
+    }
+
+    fn get_absolute_length(&self) -> usize {
+        match self.len {
+            Length::Absolute(l) => l,
+            Length::Proportional(p) => {
+                if let Some(x) = self.with_output(Output::terminal_width) {
+                    (x as f64 * p.clamp(0., 1.)) as usize
+                } else {
+                    50
+                }
+            }
+        }
+    }
+
+    fn get_ratio(&self) -> Option<f64> {
+        self.total.map(|total| self.pos as f64 / total as f64)
+    }
+
+    /// Creates the bar with the given length including the brackets
+    fn create_bar(&self, len: usize, ansi: bool) -> String {
+        let style = &self.style;
+        let mut steps = 1;
+        let additional_chars = style.brackets_len();
+        if len > additional_chars + 1 {
+            steps = len - additional_chars;
+        }
+        let (fill, empty) = if ansi {
+            (self.get_color(self.colors.fill), self.colors.empty)
+        } else {
+            (None, None)
+        };
+        let mut buf = style.left.clone();
+        let mut push = |c: char, n: usize, color: Option<Color>| {
+            buf.push_str(&color::paint(&c.to_string().repeat(n), color))
+        };
+        match self.total {
+            Some(0) => push(style.fill, steps, fill),
+            Some(_) if self.smooth && self.with_output(Output::is_utf8) => {
+                let ratio = self.get_ratio().unwrap_or(0.).min(1.);
+                let filled = ratio * steps as f64;
+                let num_symbols = filled as usize;
+                let eighths = ((filled - num_symbols as f64) * 8.) as usize;
+                push('█', num_symbols, fill);
+                if eighths > 0 {
+                    push(EIGHTHS[eighths - 1], 1, fill);
+                    push(style.empty, steps - num_symbols - 1, empty);
+                } else {
+                    push(style.empty, steps - num_symbols, empty);
+                }
+            }
+            Some(_) => {
+                let ratio = self.get_ratio().unwrap_or(0.).min(1.);
+                let num_symbols = (ratio * steps as f64) as usize;
+                push(style.fill, num_symbols, fill);
+                match style.head {
+                    Some(head) if num_symbols < steps => {
+                        push(head, 1, fill);
+                        push(style.empty, steps - num_symbols - 1, empty);
+                    }
+                    _ => push(style.empty, steps - num_symbols, empty),
+                }
+            }
+            None => {
+                // The block moves one cell per frame, no matter how far the position advanced
+                let (pos, width) = bounce(self.frames, steps);
+                push(style.empty, pos, empty);
+                push(style.fill, width, fill);
+                push(style.empty, steps - pos - width, empty);
+            }
+        }
+        buf.push_str(&style.right);
+        buf
+    }
+
+    /// Returns the color of the current state, or the supplied one while the progress bar is running normally
+    fn get_color(&self, color: Option<Color>) -> Option<Color> {
+        let state_color = match self.state {
+            State::Finished => self.colors.finished,
+            State::Abandoned => self.colors.abandoned,
+            State::Running if self.is_stalled() => self.colors.stalled,
+            State::Running => None,
+        };
+        state_color.or(color)
+    }
+
+    /// Returns whether the position didn't change for the stall timeout
+    fn is_stalled(&self) -> bool {
+        self.last_change
+            .is_some_and(|time| time.elapsed() >= self.colors.stall_timeout)
+    }
+
+    fn get_eta(&self) -> Option<Duration> {
+        let remaining = self.total?.saturating_sub(self.pos);
+        match self.rate {
+            // An eta, that is too large for a Duration, is as good as unknown
+            Some(rate) if rate > 0. => Duration::try_from_secs_f64(remaining as f64 / rate).ok(),
+            _ => None,
+        }
+    }
+
+    fn get_percentage(&self) -> Option<f64> {
+        let percentage = self.get_ratio()? * 100.;
+        if percentage > 100. || percentage.is_nan() {
+            Some(100.)
+        } else {
+            Some(percentage)
+        }
+    }
+
+    fn get_status(&self) -> String {
+        let mut status = match (self.get_percentage(), self.total) {
+            (Some(percentage), Some(total)) => {
+                let percentage = format!("{:3.0}%", percentage);
+                let total = self.unit.amount(total);
+                let counter = format!(
+                    "{:>w$}/{}",
+                    self.unit.amount(self.pos),
+                    total,
+                    w = format::visible_len(&total)
+                );
+                match self.indicator {
+                    Indicator::Percentage => format!(" ({})", percentage),
+                    Indicator::Counter => format!(" ({})", counter),
+                    Indicator::Both => format!(" ({} {})", percentage, counter),
+                }
+            }
+            _ => format!(" {}", self.unit.count(self.pos)),
+        };
+        let mut times = Vec::new();
+        if self.show_elapsed {
+            let elapsed = self.start.map(|s| s.elapsed()).unwrap_or_default();
+            times.push(format::duration(elapsed));
+        }
+        if self.show_eta && self.total.is_some() {
+            times.push(match self.get_eta() {
+                Some(eta) => format::duration(eta),
+                None => String::from("?"),
+            });
+        }
+        let mut info = Vec::new();
+        if !times.is_empty() {
+            info.push(times.join("<"));
+        }
+        if self.show_rate {
+            info.push(self.unit.rate(self.rate));
+        }
+        if !info.is_empty() {
+            status.push_str(&format!(" [{}]", info.join(", ")));
+        }
+        status
+    }
+
+    fn get_value(&self, key: Key) -> String {
+        let unknown = || String::from("?");
+        match key {
+            Key::Bar => String::new(),
+            Key::Percent => self
+                .get_percentage()
+                .map_or_else(unknown, |p| format!("{:.0}", p)),
+            Key::Pos => self.unit.amount(self.pos),
+            Key::Len => self
+                .total
+                .map_or_else(unknown, |total| self.unit.amount(total)),
+            Key::Elapsed => format::duration(self.start.map(|s| s.elapsed()).unwrap_or_default()),
+            Key::Eta => self.get_eta().map_or_else(unknown, format::duration),
+            Key::Rate => self.unit.rate(self.rate),
+            Key::Desc => self.labels.get().desc,
+            Key::Postfix => self.labels.get().postfix,
+        }
+    }
+
+    fn create_frame(&self, ansi: bool) -> String {
+        let status_color = if ansi {
+            self.get_color(self.colors.status)
+        } else {
+            None
+        };
+        let template = match &self.template {
+            Some(template) => template,
+            None => {
+                let texts = self.labels.get();
+                let mut desc = texts.desc;
+                if !desc.is_empty() {
+                    desc.push_str(": ");
+                }
+                let mut status = self.get_status();
+                if !texts.postfix.is_empty() {
+                    status.push(' ');
+                    status.push_str(&texts.postfix);
+                }
+                let length = self.get_absolute_length();
+                let status_len = format::visible_len(&status);
+                // A long description is cut, so the bar keeps at least one step and the frame fits
+                let desc = format::truncate(
+                    &desc,
+                    length.saturating_sub(status_len + self.style.brackets_len() + 1),
+                );
+                let len = length.saturating_sub(format::visible_len(&desc) + status_len);
+                return color::paint(&desc, status_color)
+                    + &self.create_bar(len, ansi)
+                    + &color::paint(&status, status_color);
+            }
+        };
+        let parts = template.render(|key| self.get_value(key));
+        let mut text_len = 0;
+        let mut bars = 0;
+        for part in &parts {
+            match part {
+                Rendered::Text(text) => text_len += format::visible_len(text),
+                Rendered::Bar(Some(len)) => text_len += len,
+                Rendered::Bar(None) => bars += 1,
+            }
+        }
+        // The bars without a length share the remaining length
+        let len = self.get_absolute_length().saturating_sub(text_len) / bars.max(1);
+        parts
+            .into_iter()
+            .map(|part| match part {
+                Rendered::Text(text) => color::paint(&text, status_color),
+                Rendered::Bar(bar_len) => self.create_bar(bar_len.unwrap_or(len), ansi),
+            })
+            .collect()
+    }
+
+    fn update_rate(&mut self, now: Instant) {
+        if let Some((time, pos)) = self.last_draw {
+            let secs = now.duration_since(time).as_secs_f64();
+            if self.pos > pos && secs > 0. {
+                let rate = (self.pos - pos) as f64 / secs;
+                self.rate = Some(match self.rate {
+                    Some(r) => SMOOTHING * rate + (1. - SMOOTHING) * r,
+                    None => rate,
+                });
+            }
+        }
+    }
+
+    fn should_draw(&self) -> bool {
+        match self.last_draw {
+            None => true,
+            Some((time, pos)) => {
+                // Moving backwards counts as well, so a lower position is drawn
+                self.pos.abs_diff(pos) >= self.min_iterations && time.elapsed() >= self.min_interval
+            }
+        }
+    }
+
+    pub(crate) fn draw(&mut self) {
+        let now = Instant::now();
+        self.update_rate(now);
+        self.last_draw = Some((now, self.pos));
+        if self.is_logging() {
+            self.log(false);
+            return;
+        }
+        let ansi = self.with_output(Output::is_terminal) && !color::no_color();
+        let frame = self.create_frame(ansi);
+        self.frames = self.frames.wrapping_add(1);
+        if frame == self.last_frame {
+            return;
+        }
+        if let Some(slot) = &self.slot {
+            slot.draw(&frame);
+            self.last_frame = frame;
+            return;
+        }
+        // Only the part of the last frame, that is longer than the new one, has to be overwritten with whitespaces
+        let len = format::visible_len(&frame);
+        let last_len = format::visible_len(&self.last_frame);
+        let mut buf = String::with_capacity(frame.len() + 1);
+        buf.push('\r');
+        buf.push_str(&frame);
+        buf.push_str(&" ".repeat(last_len.saturating_sub(len)));
+        screen::update(&mut self.output, &[&frame], |output| {
+            output.write_all(buf.as_bytes()).ok();
+            output.flush().ok();
+        });
+        self.last_frame = frame;
+    }
+}
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        // The progress bar wasn't finished, so it is left on its own line
+        if self.start.is_some() {
+            self.abandon();
+        }
+    }
+}
+
+/// Returns the position and the width of the block, that bounces back and forth inside the bar in the given frame, when the total is unknown
+fn bounce(frame: u64, steps: usize) -> (usize, usize) {
+    let width = steps.min(3);
+    let positions = steps - width;
+    if positions == 0 {
+        return (0, width);
+    }
+    let t = (frame % (2 * positions) as u64) as usize;
+    if t <= positions {
+        (t, width)
+    } else {
+        (2 * positions - t, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{plain, Buffer};
+    use std::io;
+
+    #[test]
+    fn test_bounce() {
+        assert_eq!(bounce(0, 5), (0, 3));
+        assert_eq!(bounce(2, 5), (2, 3));
+        assert_eq!(bounce(3, 5), (1, 3));
+        assert_eq!(bounce(4, 5), (0, 3));
+        assert_eq!(bounce(7, 2), (0, 2));
+    }
+
+    #[test]
+    fn test_bar() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(4), &buf);
+        bar.inc(1);
+        bar.inc(1);
+        bar.set_position(4);
+        bar.finish();
+        bar.finish();
+        assert_eq!(
+            buf.contents(),
+            "\r[#   ] ( 25%)\r[##  ] ( 50%)\r[####] (100%)\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_total() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::with_unknown_total(), &buf).set_length_move(Length::Absolute(16));
+        for _ in 0..3 {
+            bar.inc(1000);
+        }
+        assert_eq!(
+            buf.contents(),
+            "\r[###    ] 1000it\r[ ###   ] 2000it\r[  ###  ] 3000it"
+        );
+    }
+
+    #[test]
+    fn test_abandon() {
+        let buf = Buffer::default();
+        let mut bar =
+            plain(Bar::with_unknown_total(), &buf).set_min_interval_move(Duration::from_secs(3600));
+        bar.inc(2);
+        bar.set_total(4);
+        drop(bar);
+        assert_eq!(buf.contents(), "\r[###    ] 2it\r[##  ] ( 50%)\n");
+    }
+
+    #[test]
+    fn test_tick() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(4), &buf).set_min_interval_move(Duration::from_secs(3600));
+        bar.tick();
+        assert!(bar.start.is_some());
+        bar.inc(2);
+        bar.tick();
+        bar.finish();
+        bar.tick();
+        assert_eq!(buf.contents(), "\r[    ] (  0%)\r[##  ] ( 50%)\n");
+    }
+
+    #[test]
+    fn test_leave() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(4), &buf).set_leave_move(false);
+        bar.inc(2);
+        bar.finish();
+        assert_eq!(buf.contents(), "\r[##  ] ( 50%)\r             \r");
+    }
+
+    #[test]
+    fn test_backwards() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(100), &buf);
+        bar.set_position(50);
+        bar.set_position(10);
+        bar.set_position(20);
+        assert_eq!(
+            buf.contents(),
+            "\r[##  ] ( 50%)\r[    ] ( 10%)\r[    ] ( 20%)"
+        );
+    }
+
+    #[test]
+    fn test_writeln_not_started() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(4), &buf);
+        bar.writeln("hello").unwrap();
+        drop(bar);
+        assert_eq!(buf.contents(), "\r\rhello\n");
+    }
+
+    #[test]
+    fn test_writeln_ended() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(4), &buf);
+        bar.inc(4);
+        bar.finish();
+        bar.writeln("done").unwrap();
+        assert_eq!(buf.contents(), "\r[####] (100%)\ndone\n");
+    }
+
+    #[test]
+    fn test_status() {
+        let mut bar = Bar::new(100).set_output_move(Output::writer(io::sink()));
+        bar.inc(1);
+        assert_eq!(bar.get_status(), " (  1%) [00:00<?, ?it/s]");
+        bar.pos = 50;
+        bar.rate = Some(10.);
+        assert_eq!(bar.get_status(), " ( 50%) [00:00<00:05, 10.00it/s]");
+        bar.set_show_elapsed(false);
+        bar.set_show_rate(false);
+        assert_eq!(bar.get_status(), " ( 50%) [00:05]");
+        bar.set_show_eta(false);
+        assert_eq!(bar.get_status(), " ( 50%)");
+        bar.set_indicator(Indicator::Both);
+        assert_eq!(bar.get_status(), " ( 50%  50/100)");
+        bar.set_indicator(Indicator::Counter);
+        bar.set_unit(Unit::BinaryBytes);
+        assert_eq!(bar.get_status(), " ( 50 B/100 B)");
+        bar.total = Some(3 << 20);
+        bar.pos = 1 << 19;
+        assert_eq!(bar.get_status(), " ( 512 KiB/3.00 MiB)");
+        bar.set_show_rate(true);
+        assert_eq!(bar.get_status(), " ( 512 KiB/3.00 MiB) [10 B/s]");
+    }
+
+    #[test]
+    fn test_wide_desc() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(4), &buf)
+            .set_length_move(Length::Absolute(17))
+            .set_desc_move("下载");
+        bar.pos = 2;
+        assert_eq!(bar.create_frame(false), "下载: [# ] ( 50%)");
+        bar.set_length(Length::Absolute(13));
+        assert_eq!(bar.create_frame(false), "下[# ] ( 50%)");
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(100), &buf).set_indicator_move(Indicator::Both);
+        bar.pos = 99;
+        bar.draw();
+        bar.set_indicator(Indicator::Percentage);
+        bar.draw();
+        assert_eq!(buf.contents(), "\r[ ] ( 99%  99/100)\r[### ] ( 99%)     ");
+    }
+
+    #[test]
+    fn test_non_terminal() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(100), &buf)
+            .set_non_terminal_move(NonTerminal::Percentage(25.))
+            .set_desc_move("copy");
+        for _ in 0..60 {
+            bar.inc(1);
+        }
+        bar.writeln("text").unwrap();
+        bar.finish();
+        assert_eq!(
+            buf.contents(),
+            "copy: 1% 1/100\ncopy: 25% 25/100\ncopy: 50% 50/100\ntext\ncopy: 60% 60/100\n"
+        );
+
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(100), &buf)
+            .set_non_terminal_move(NonTerminal::Interval(Duration::from_secs(3600)));
+        bar.inc(1);
+        bar.inc(1);
+        bar.finish();
+        assert_eq!(buf.contents(), "1% 1/100\n2% 2/100\n");
+
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(100), &buf).set_non_terminal_move(NonTerminal::Silent);
+        bar.inc(1);
+        bar.finish();
+        assert_eq!(buf.contents(), "");
+    }
+
+    #[test]
+    fn test_last_log_rate() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::with_unknown_total(), &buf)
+            .set_min_interval_move(Duration::from_secs(3600))
+            .set_show_rate_move(true)
+            .set_non_terminal_move(NonTerminal::Interval(Duration::from_secs(3600)));
+        for _ in 0..3000 {
+            bar.inc(1);
+        }
+        bar.finish();
+        let contents = buf.contents();
+        let last = contents.lines().last().unwrap();
+        assert!(last.starts_with("3000it "));
+        assert!(last.ends_with("it/s"));
+        assert!(!last.ends_with("?it/s"));
+    }
+
+    #[test]
+    fn test_style() {
+        let mut bar = Bar::new(4);
+        bar.pos = 2;
+        assert_eq!(bar.create_bar(6, false), "[##  ]");
+        bar.set_style(Style::arrow());
+        assert_eq!(bar.create_bar(6, false), "[==> ]");
+        bar.set_style(Style::blocks());
+        assert_eq!(bar.create_bar(6, false), "|██░░|");
+        bar.set_style(Style {
+            left: String::new(),
+            right: String::new(),
+            ..Style::dots()
+        });
+        assert_eq!(bar.create_bar(6, false), "●●●···");
+        bar.pos = 4;
+        bar.set_style(Style::arrow());
+        assert_eq!(bar.create_bar(6, false), "[====]");
+    }
+
+    #[test]
+    fn test_smooth() {
+        let mut bar = Bar::new(32)
+            .set_smooth_move(true)
+            .set_output_move(Output::writer(io::sink()));
+        assert_eq!(bar.create_bar(6, false), "[    ]");
+        bar.pos = 1;
+        assert_eq!(bar.create_bar(6, false), "[▏   ]");
+        bar.pos = 15;
+        assert_eq!(bar.create_bar(6, false), "[█▉  ]");
+        bar.pos = 32;
+        assert_eq!(bar.create_bar(6, false), "[████]");
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut bar = Bar::new(4).set_colors_move(Colors {
+            fill: Some(Color::Blue),
+            ..Colors::states()
+        });
+        bar.pos = 2;
+        assert_eq!(bar.create_bar(6, false), "[##  ]");
+        assert_eq!(bar.create_bar(6, true), "[\x1b[34m##\x1b[0m  ]");
+        bar.last_change = Instant::now().checked_sub(Duration::from_secs(10));
+        assert_eq!(bar.create_bar(6, true), "[\x1b[33m##\x1b[0m  ]");
+        bar.last_change = Some(Instant::now());
+        assert_eq!(bar.create_bar(6, true), "[\x1b[34m##\x1b[0m  ]");
+        bar.last_change = Instant::now().checked_sub(Duration::from_secs(10));
+        bar.state = State::Abandoned;
+        assert_eq!(bar.create_bar(6, true), "[\x1b[31m##\x1b[0m  ]");
+        bar.pos = 4;
+        bar.state = State::Finished;
+        assert_eq!(bar.create_bar(6, true), "[\x1b[32m####\x1b[0m]");
+    }
+
+    #[test]
+    fn test_rate() {
+        let mut bar = Bar::new(100);
+        let start = Instant::now();
+        bar.last_draw = Some((start, 0));
+        bar.pos = 10;
+        bar.update_rate(start + Duration::from_secs(1));
+        assert_eq!(bar.rate, Some(10.));
+        bar.last_draw = Some((start, 0));
+        bar.pos = 20;
+        bar.update_rate(start + Duration::from_secs(1));
+        assert_eq!(bar.rate, Some(13.));
+    }
+
+    #[test]
+    fn test_huge_eta() {
+        let buf = Buffer::default();
+        let mut bar = plain(Bar::new(u64::MAX), &buf).set_show_eta_move(true);
+        bar.inc(1);
+        bar.rate = Some(0.5);
+        assert_eq!(bar.get_eta(), None);
+        assert_eq!(bar.get_status(), " (  0%) [?]");
+        bar.draw();
+    }
+}
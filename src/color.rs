@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+/// The colors, that can be used in [Colors](struct.Colors.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn code(self) -> u8 {
+        match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+        }
+    }
+}
+
+/// Use this struct to [set the colors](struct.Prgrs.html#method.set_colors) of the progress bar.
+///
+/// Every field, that is `None`, is drawn in the default color of the terminal.
+/// The `finished`, `abandoned` and `stalled` colors replace the colors of the filled part and the status, while the progress bar is in that state.
+/// A stalled progress bar is only noticed, when it is drawn, so call [Bar::tick()](struct.Bar.html#method.tick) regularly, while waiting for progress.
+///
+/// Colors are only used, when the output is a terminal and the `NO_COLOR` environment variable isn't set.
+/// # Example
+/// ```
+/// use prgrs::{Color, Colors};
+/// let colors = Colors {
+///     fill: Some(Color::Blue),
+///     ..Colors::states()
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colors {
+    /// The color of the filled part of the bar
+    pub fill: Option<Color>,
+    /// The color of the empty part of the bar
+    pub empty: Option<Color>,
+    /// The color of everything shown after the bar, like the percentage
+    pub status: Option<Color>,
+    /// Used once the Iterator is finished
+    pub finished: Option<Color>,
+    /// Used when the progress bar is dropped before the Iterator is finished, for example because the loop was left with `break`
+    pub abandoned: Option<Color>,
+    /// Used when the position didn't change for `stall_timeout`
+    pub stalled: Option<Color>,
+    /// How long the position has to stay the same, until the progress bar is considered stalled
+    pub stall_timeout: Duration,
+}
+
+impl Colors {
+    /// No colors at all, this is the default
+    pub fn none() -> Self {
+        Colors {
+            fill: None,
+            empty: None,
+            status: None,
+            finished: None,
+            abandoned: None,
+            stalled: None,
+            stall_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Green when finished, red when abandoned and yellow when stalled
+    pub fn states() -> Self {
+        Colors {
+            finished: Some(Color::Green),
+            abandoned: Some(Color::Red),
+            stalled: Some(Color::Yellow),
+            ..Colors::none()
+        }
+    }
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors::none()
+    }
+}
+
+/// Returns whether the `NO_COLOR` environment variable asks to not use any colors
+pub(crate) fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|val| !val.is_empty())
+}
+
+/// Wraps the text in the SGR sequences for the given color
+pub(crate) fn paint(text: &str, color: Option<Color>) -> String {
+    match color {
+        Some(color) if !text.is_empty() => format!("\x1b[{}m{}\x1b[0m", color.code(), text),
+        _ => String::from(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(paint("###", Some(Color::Green)), "\x1b[32m###\x1b[0m");
+        assert_eq!(paint("###", None), "###");
+        assert_eq!(paint("", Some(Color::Red)), "");
+    }
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Formats a duration like `05:42`, or `1:05:42` once it takes longer than an hour
+pub(crate) fn duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+/// Splits the text into the visible parts and the ANSI escape sequences between them, the latter are marked with `true`
+fn split_escapes(text: &str) -> Vec<(&str, bool)> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        let end = start + escape_len(&rest[start..]);
+        parts.push((&rest[..start], false));
+        parts.push((&rest[start..end], true));
+        rest = &rest[end..];
+    }
+    parts.push((rest, false));
+    parts
+}
+
+/// Returns the length of the escape sequence at the start of the text, which is cut off at the end of the text
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let end = match bytes.get(1) {
+        // CSI like colors `ESC [ 32 m`, which ends with a byte between `@` and `~`
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (b'@'..=b'~').contains(b))
+            .map(|i| i + 3),
+        // OSC like hyperlinks `ESC ] 8 ; ; url ESC \`, which ends with BEL or ST
+        Some(b']') => (2..bytes.len()).find_map(|i| match (bytes[i], bytes.get(i + 1)) {
+            (0x07, _) => Some(i + 1),
+            (0x1b, Some(b'\\')) => Some(i + 2),
+            _ => None,
+        }),
+        // Any other sequence ends with the first character after the intermediate bytes
+        _ => {
+            let i = 1 + bytes[1..]
+                .iter()
+                .take_while(|b| (0x20..=0x2f).contains(*b))
+                .count();
+            text[i..].chars().next().map(|c| i + c.len_utf8())
+        }
+    };
+    end.unwrap_or(text.len())
+}
+
+/// The number of columns the text takes up in a terminal.
+///
+/// ANSI escape sequences are skipped, wide characters like most CJK characters take up two columns and every grapheme cluster is measured as a whole.
+pub(crate) fn visible_len(text: &str) -> usize {
+    split_escapes(text)
+        .into_iter()
+        .filter(|(_, escape)| !escape)
+        .flat_map(|(part, _)| part.graphemes(true))
+        .map(UnicodeWidthStr::width)
+        .sum()
+}
+
+/// Cuts the text, so it takes up at most `width` columns, without splitting a grapheme cluster.
+///
+/// ANSI escape sequences are kept, so colors are still reset at the end.
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    let mut buf = String::with_capacity(text.len());
+    let mut len = 0;
+    for (part, escape) in split_escapes(text) {
+        if escape {
+            buf.push_str(part);
+            continue;
+        }
+        for grapheme in part.graphemes(true) {
+            len += grapheme.width();
+            if len > width {
+                break;
+            }
+            buf.push_str(grapheme);
+        }
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(duration(Duration::from_secs(0)), "00:00");
+        assert_eq!(duration(Duration::from_secs(342)), "05:42");
+        assert_eq!(duration(Duration::from_secs(3942)), "1:05:42");
+    }
+
+    #[test]
+    fn test_visible_len() {
+        assert_eq!(visible_len("[##  ]"), 6);
+        assert_eq!(visible_len("[\x1b[32m##\x1b[0m  ]"), 6);
+        assert_eq!(visible_len("größe"), 5);
+        assert_eq!(visible_len("下载"), 4);
+        assert_eq!(visible_len("e\u{301}"), 1);
+        assert_eq!(visible_len("👨\u{200d}👩\u{200d}👧"), 2);
+        assert_eq!(
+            visible_len("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"),
+            4
+        );
+        assert_eq!(
+            visible_len("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"),
+            4
+        );
+        assert_eq!(visible_len("\x1b(Babc"), 3);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("download", 4), "down");
+        assert_eq!(truncate("download", 10), "download");
+        assert_eq!(truncate("下载中", 3), "下");
+        assert_eq!(truncate("e\u{301}e\u{301}", 1), "e\u{301}");
+        assert_eq!(truncate("\x1b[32mdownload\x1b[0m", 2), "\x1b[32mdo\x1b[0m");
+        assert_eq!(
+            truncate("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\", 2),
+            "\x1b]8;;https://example.com\x1b\\li\x1b]8;;\x1b\\"
+        );
+    }
+}
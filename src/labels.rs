@@ -0,0 +1,49 @@
+use crate::lock_unpoisoned;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A handle to update the description and the postfix of a progress bar, while it is iterated over.
+///
+/// Get one with [Prgrs::labels()](struct.Prgrs.html#method.labels) before the loop, since the progress bar is moved into it.
+/// The new text is shown the next time the progress bar is drawn.
+///
+/// The handle can be cloned and sent to other threads.
+/// # Example
+/// ```
+/// use prgrs::Prgrs;
+/// let p = Prgrs::new(0..100, 100);
+/// let labels = p.labels();
+/// for i in p {
+///     labels.set_postfix(&format!("loss={:.3}", 1. / (i + 1) as f64));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Labels {
+    inner: Arc<Mutex<Texts>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Texts {
+    pub(crate) desc: String,
+    pub(crate) postfix: String,
+}
+
+impl Labels {
+    /// Set the description shown in front of the progress bar
+    pub fn set_desc(&self, desc: &str) {
+        self.lock().desc = String::from(desc);
+    }
+
+    /// Set the postfix shown at the end of the progress line
+    pub fn set_postfix(&self, postfix: &str) {
+        self.lock().postfix = String::from(postfix);
+    }
+
+    /// Returns a copy of the current texts
+    pub(crate) fn get(&self) -> Texts {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Texts> {
+        lock_unpoisoned(&self.inner)
+    }
+}
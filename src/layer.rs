@@ -0,0 +1,247 @@
+use crate::bar::Bar;
+use crate::lock_unpoisoned;
+use crate::multi::MultiBar;
+use crate::screen::suspend;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::sync::{Mutex, MutexGuard};
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// The field of a span, that holds the total of its progress bar
+const TOTAL: &str = "progress.total";
+/// The field of an event, that advances the progress bar of its span
+const INC: &str = "progress.inc";
+
+/// A tracing layer, that shows a progress bar for every span with a `progress.total` field, this struct is available with the `tracing` feature.
+///
+/// The progress bar is described with the name of the span and advanced by every event inside the span, that has a `progress.inc` field.
+/// Events in nested spans advance the closest span with a progress bar.
+/// It is finished, once the span is closed.
+/// The progress bars of all spans are drawn below each other with a [MultiBar](struct.MultiBar.html).
+/// To print the formatted output of tracing above them, use a [SuspendWriter](struct.SuspendWriter.html).
+/// # Example
+/// ```
+/// use prgrs::{ProgressLayer, SuspendWriter};
+/// use tracing_subscriber::layer::SubscriberExt;
+/// use tracing_subscriber::Registry;
+///
+/// let subscriber = Registry::default()
+///     .with(ProgressLayer::new())
+///     .with(tracing_subscriber::fmt::layer().with_writer(SuspendWriter::stderr()));
+/// tracing::subscriber::with_default(subscriber, || {
+///     let span = tracing::info_span!("download", progress.total = 100);
+///     let _entered = span.enter();
+///     for i in 0..100 {
+///         // do something here
+///         tracing::trace!(progress.inc = 1, "{} done", i);
+///     }
+/// });
+/// ```
+#[derive(Default)]
+pub struct ProgressLayer {
+    multi: MultiBar,
+}
+
+impl ProgressLayer {
+    /// Creates a ProgressLayer, that draws the progress bars to stderr
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a ProgressLayer, that adds the progress bars to the given MultiBar, which can be configured beforehand
+    /// # Example
+    /// ```
+    /// use prgrs::{MultiBar, Output, ProgressLayer};
+    /// let layer = ProgressLayer::with_multi(MultiBar::new().set_output_move(Output::Stdout));
+    /// ```
+    pub fn with_multi(multi: MultiBar) -> Self {
+        ProgressLayer { multi }
+    }
+
+    fn start(&self, name: &str, total: u64) -> SpanBar {
+        let mut bar = self.multi.add(Bar::new(total).set_desc_move(name));
+        // Shows the bar right away and starts its timer with the span
+        bar.set_position(0);
+        SpanBar(Mutex::new(bar))
+    }
+}
+
+/// The progress bar of a span, which is stored in the extensions of the span
+struct SpanBar(Mutex<Bar>);
+
+impl SpanBar {
+    fn lock(&self) -> MutexGuard<'_, Bar> {
+        lock_unpoisoned(&self.0)
+    }
+}
+
+/// Collects the progress fields of a span or an event
+#[derive(Default)]
+struct Fields {
+    total: Option<u64>,
+    inc: Option<u64>,
+}
+
+impl Visit for Fields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            TOTAL => self.total = Some(value),
+            INC => self.inc = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        // Integer literals are recorded as i64, negative values are ignored
+        if let Ok(value) = u64::try_from(value) {
+            self.record_u64(field, value);
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+}
+
+impl<S> Layer<S> for ProgressLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let (Some(total), Some(span)) = (fields.total, ctx.span(id)) {
+            span.extensions_mut().insert(self.start(span.name(), total));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if let (Some(total), Some(span)) = (fields.total, ctx.span(id)) {
+            let mut extensions = span.extensions_mut();
+            match extensions.get_mut::<SpanBar>() {
+                Some(bar) => bar.lock().set_total(total),
+                // The total was left empty, when the span was created
+                None => extensions.insert(self.start(span.name(), total)),
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        if let (Some(inc), Some(scope)) = (fields.inc, ctx.event_scope(event)) {
+            for span in scope {
+                if let Some(bar) = span.extensions().get::<SpanBar>() {
+                    bar.lock().inc(inc);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(bar) = span.extensions_mut().remove::<SpanBar>() {
+                bar.lock().finish();
+            }
+        }
+    }
+}
+
+/// Writes to stderr or stdout, while all progress bars are [suspended](fn.suspend.html), this struct is available with the `tracing` feature.
+///
+/// Pass it to `with_writer()` of a tracing fmt layer or subscriber, so its output is printed above the progress bars.
+/// # Example
+/// ```
+/// use prgrs::SuspendWriter;
+/// tracing_subscriber::fmt()
+///     .with_writer(SuspendWriter::stderr())
+///     .init();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SuspendWriter {
+    stdout: bool,
+}
+
+impl SuspendWriter {
+    /// Creates a SuspendWriter, that writes to stderr
+    pub fn stderr() -> Self {
+        SuspendWriter { stdout: false }
+    }
+
+    /// Creates a SuspendWriter, that writes to stdout
+    pub fn stdout() -> Self {
+        SuspendWriter { stdout: true }
+    }
+}
+
+impl Write for SuspendWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The whole buffer is written at once, so a message isn't interrupted by a redraw
+        suspend(|| {
+            if self.stdout {
+                let mut stdout = io::stdout();
+                stdout.write_all(buf)?;
+                stdout.flush()?;
+            } else {
+                io::stderr().write_all(buf)?;
+            }
+            Ok(buf.len())
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.stdout {
+            io::stdout().flush()
+        } else {
+            io::stderr().flush()
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for SuspendWriter {
+    type Writer = SuspendWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Output;
+    use crate::tests::Buffer;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn test_layer() {
+        let buf = Buffer::default();
+        let multi = MultiBar::new().set_output_move(Output::writer(buf.clone()));
+        let subscriber = Registry::default().with(ProgressLayer::with_multi(multi));
+        tracing::subscriber::with_default(subscriber, || {
+            let download = tracing::info_span!("download", progress.total = 4);
+            let _download = download.enter();
+            for _ in 0..2 {
+                let chunk = tracing::info_span!("chunk");
+                let _chunk = chunk.enter();
+                tracing::info!(progress.inc = 2u64);
+                tracing::info!("ignored");
+            }
+            let unpack = tracing::info_span!("unpack", progress.total = tracing::field::Empty);
+            unpack.record("progress.total", 3);
+            unpack.in_scope(|| tracing::info!(progress.inc = 3));
+        });
+        let contents = buf.contents();
+        assert!(contents.starts_with("download: 0% 0/4"));
+        assert!(contents.contains("\nunpack: 0% 0/3"));
+        assert!(contents.contains("\nunpack: 100% 3/3"));
+        assert!(contents.contains("\ndownload: 100% 4/4"));
+    }
+}
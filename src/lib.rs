@@ -17,20 +17,72 @@
 //!
 //! The output will look something like this:
 //!
-//! `[##############                     ] ( 42%)`
+//! `[##############                     ] ( 42%) [00:04<00:05, 98.76it/s]`
 //!
-use std::io::{self, Error, ErrorKind, Write};
-use terminal_size::{terminal_size, Height, Width};
+//! # Features
+//! These optional cargo features integrate prgrs with other crates:
+//!
+//! - `rayon`: [progress()](trait.ParallelPrgrsExt.html#method.progress) for parallel iterators
+//! - `futures`: [PrgrsStream](struct.PrgrsStream.html) for streams, and `AsyncRead`/`AsyncWrite` for [ProgressReader](struct.ProgressReader.html) and [ProgressWriter](struct.ProgressWriter.html)
+//! - `log`: [LogWrapper](struct.LogWrapper.html) to print log messages above the progress bars
+//! - `tracing`: [ProgressLayer](struct.ProgressLayer.html) to show progress bars for spans, and [SuspendWriter](struct.SuspendWriter.html) to print tracing's output above them
+//!
+use std::io::{self, Error, Write};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+mod adapter;
+mod bar;
+mod color;
+mod format;
+mod labels;
+#[cfg(feature = "tracing")]
+mod layer;
+#[cfg(feature = "log")]
+mod logger;
+mod multi;
+mod output;
+#[cfg(feature = "rayon")]
+mod par;
+mod screen;
+mod shared;
+#[cfg(feature = "futures")]
+mod stream;
+mod style;
+mod template;
+mod unit;
+
+pub use adapter::{ProgressReader, ProgressWriter};
+pub use bar::Bar;
+pub use color::{Color, Colors};
+pub use labels::Labels;
+#[cfg(feature = "tracing")]
+pub use layer::{ProgressLayer, SuspendWriter};
+#[cfg(feature = "log")]
+pub use logger::LogWrapper;
+pub use multi::MultiBar;
+pub use output::{NonTerminal, Output};
+#[cfg(feature = "rayon")]
+pub use par::{ParPrgrs, ParallelPrgrsExt};
+pub use screen::suspend;
+pub use shared::SharedBar;
+#[cfg(feature = "futures")]
+pub use stream::{PrgrsStream, PrgrsStreamExt};
+pub use style::Style;
+pub use template::{Template, TemplateError};
+pub use unit::Unit;
 
 pub struct Prgrs<T: Iterator> {
     iter: T,
-    size: usize,
-    curr: usize,
-    len: Length,
+    bar: Bar,
+    weight: Option<Weight<T::Item>>,
 }
 
+/// Computes how much an element advances the progress bar
+type Weight<I> = Box<dyn Fn(&I) -> u64 + Send>;
+
 /// Use this struct to [set the length](struct.Prgrs.html#method.set_length) of the progress bar.
-/// The lengths include the percentage count and parentheses at the end of the bar.
+/// The lengths include the percentage count and everything else shown at the end of the bar.
 /// # Proportional (better use this whenever possible)
 /// When using the Proportional variant values below 0. are rounded to 0. and values above 1. are rounded to 1.
 ///
@@ -44,7 +96,7 @@ pub struct Prgrs<T: Iterator> {
 ///
 /// Values, that would make make the bar smaller than a single step however like negative values or for example 2 are ignored and the bar will have a single step.
 pub enum Length {
-    /// Used to set the absolute length of the progress bar in characters
+    /// Used to set the absolute length of the progress bar in terminal columns, wide characters like CJK take up two of them
     Absolute(usize),
     /// Used to set the length proportional to the width of your terminal
     ///
@@ -52,6 +104,19 @@ pub enum Length {
     Proportional(f64),
 }
 
+/// Use this enum to [set the indicator](struct.Prgrs.html#method.set_indicator), that shows how far the progress bar is.
+///
+/// The counter is right-aligned to the width of the total, so the bar doesn't jitter while the counter grows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indicator {
+    /// Show the percentage, like `( 42%)`
+    Percentage,
+    /// Show the current iteration and the total, like `( 420/1000)`
+    Counter,
+    /// Show both, like `( 42%  420/1000)`
+    Both,
+}
+
 impl<T: Iterator> Prgrs<T> {
     /// Creates a new Prgrs struct.
     ///
@@ -64,14 +129,107 @@ impl<T: Iterator> Prgrs<T> {
     ///}
     /// ```
     pub fn new(it: T, size: usize) -> Self {
+        Self::with_size(it, Some(size))
+    }
+
+    /// Creates a new Prgrs struct and takes the number of elements from the Iterator itself.
+    ///
+    /// The size is only taken from the [size_hint()](https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.size_hint) of the Iterator, if its lower and upper bound are equal, which holds for every `ExactSizeIterator` like ranges or `Vec::iter()`.
+    /// Otherwise, like for `filter()` or `chars()`, the total is unknown and no percentage is shown, see [set_size()](struct.Prgrs.html#method.set_size).
+    ///
+    /// You can also use [prgrs()](trait.PrgrsExt.html#method.prgrs) on the Iterator instead.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let v = vec![1, 2, 3];
+    /// for _ in Prgrs::from_size_hint(v.iter()){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn from_size_hint(it: T) -> Self {
+        let size = exact_size(it.size_hint());
+        Self::with_size(it, size)
+    }
+
+    fn with_size(it: T, size: Option<usize>) -> Self {
         Prgrs::<T> {
             iter: it,
-            size,
-            curr: 0,
-            len: Length::Proportional(0.33),
+            bar: Bar::with_total(size.map(|size| size as u64)),
+            weight: None,
         }
     }
 
+    /// Set the number of elements in the Iterator.
+    ///
+    /// This is useful, when the total wasn't known when the progress bar was created.
+    /// As long as it's unknown, a block bouncing back and forth is shown instead of the progress, together with the number of elements processed so far, the elapsed time and the rate.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::from_size_hint((0..).take_while(|i| *i < 100));
+    /// while let Some(i) = p.next() {
+    ///     if i == 10 {
+    ///         // now we know how many elements there are
+    ///         p.set_size(100);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_size(&mut self, size: usize) {
+        self.bar.set_total(size as u64);
+    }
+
+    /// Same as [set_size()](struct.Prgrs.html#method.set_size), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let lines = "a\nb\nc".lines();
+    /// for _ in Prgrs::from_size_hint(lines).set_size_move(3){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_size_move(mut self, size: usize) -> Self {
+        self.bar.set_total(size as u64);
+        self
+    }
+
+    /// Set a function, that computes how much each element advances the progress bar, instead of advancing it by one.
+    ///
+    /// This is useful when the elements take differently long to process, like files of different sizes.
+    /// The size has to be [set](struct.Prgrs.html#method.set_size) in the same unit, like the sum of the sizes of all files.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let chunks = vec![vec![0u8; 100], vec![0u8; 300]];
+    /// let mut p = Prgrs::new(chunks.iter(), 400);
+    /// p.set_weight(|chunk| chunk.len() as u64);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_weight<F>(&mut self, weight: F)
+    where
+        F: Fn(&T::Item) -> u64 + Send + 'static,
+    {
+        self.weight = Some(Box::new(weight));
+    }
+
+    /// Same as [set_weight()](struct.Prgrs.html#method.set_weight), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let files = vec![("a.txt", 1024), ("b.txt", 4096)];
+    /// for _ in Prgrs::new(files.into_iter(), 5120).set_weight_move(|(_, size)| *size){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_weight_move<F>(mut self, weight: F) -> Self
+    where
+        F: Fn(&T::Item) -> u64 + Send + 'static,
+    {
+        self.weight = Some(Box::new(weight));
+        self
+    }
+
     /// Set the length of the progress bar. The default is `Length::Proportional(0.33)`
     ///
     /// To set an absolute value use [`Length::Absolute(val)`](enum.Length.html#variant.Absolute) and to set a proportional value use [`Length::Proportional(val)`](enum.Length.html#variant.Proportional)
@@ -93,7 +251,7 @@ impl<T: Iterator> Prgrs<T> {
     ///}
     /// ```
     pub fn set_length(&mut self, len: Length) {
-        self.len = len;
+        self.bar.set_length(len);
     }
 
     /// Same as [set_length()](struct.Prgrs.html#method.set_length), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
@@ -105,56 +263,517 @@ impl<T: Iterator> Prgrs<T> {
     ///}
     /// ```
     pub fn set_length_move(mut self, len: Length) -> Self {
-        self.len = len;
-        self
-    }
-
-    fn get_absolute_length(&self) -> usize {
-        match self.len {
-            Length::Absolute(l) => l,
-            Length::Proportional(mut p) => {
-                if let Some((Width(x), Height(_y))) = terminal_size() {
-                    if p > 1. {
-                        p = 1.;
-                    } else if p < 0. {
-                        p = 0.;
-                    }
-                    (x as f64 * p) as usize
-                } else {
-                    50
-                }
-            }
-        }
+        self.bar.set_length(len);
+        self
     }
 
-    fn get_ratio(&self) -> f64 {
-        self.curr as f64 / self.size as f64
+    /// Set the [output](enum.Output.html) the progress bar is drawn to. The default is `Output::Stderr`
+    ///
+    /// Everything the progress bar prints, including the final newline and text written with [writeln()](struct.Prgrs.html#method.writeln), goes to this output.
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Output};
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_output(Output::Stdout);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_output(&mut self, output: Output) {
+        self.bar.set_output(output);
     }
 
-    fn create_bar(&self) -> String {
-        let symbol = "#";
-        let len = self.get_absolute_length();
-        let mut steps = 1;
-        let additional_chars = "[] (100%)".len();
-        if len > additional_chars + 1 {
-            steps = len - additional_chars;
-        }
-        let mut buf = String::from("[");
-        if self.size == 0 {
-            for _ in 0..steps {
-                buf.push_str(symbol);
-            }
-        } else {
-            let num_symbols = (self.get_ratio() * steps as f64) as usize;
-            for _ in 0..num_symbols {
-                buf.push_str(symbol);
-            }
-            for _ in 0..steps - num_symbols {
-                buf.push_str(" ");
-            }
-        }
-        buf.push_str("]");
-        buf
+    /// Same as [set_output()](struct.Prgrs.html#method.set_output), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Output};
+    /// for _ in Prgrs::new(0..100, 100).set_output_move(Output::Stdout){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_output_move(mut self, output: Output) -> Self {
+        self.bar.set_output(output);
+        self
+    }
+
+    /// Set the minimum time between two redraws of the progress bar. The default is 100 milliseconds
+    ///
+    /// Redrawing the bar is a lot more expensive than most loop bodies, so it is only redrawn once this much time has passed since the last redraw.
+    /// The bar is always drawn when the Iterator is finished, so it ends up at 100% nevertheless.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// use std::time::Duration;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_min_interval(Duration::from_secs(1));
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_min_interval(&mut self, min_interval: Duration) {
+        self.bar.set_min_interval(min_interval);
+    }
+
+    /// Same as [set_min_interval()](struct.Prgrs.html#method.set_min_interval), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// use std::time::Duration;
+    /// for _ in Prgrs::new(0..100, 100).set_min_interval_move(Duration::from_secs(1)){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_min_interval_move(mut self, min_interval: Duration) -> Self {
+        self.bar.set_min_interval(min_interval);
+        self
+    }
+
+    /// Set the minimum number of iterations between two redraws of the progress bar. The default is 1
+    ///
+    /// This is checked before the [minimum interval](struct.Prgrs.html#method.set_min_interval), so for very tight loops a higher value also saves looking at the clock on every iteration.
+    /// With a [weight](struct.Prgrs.html#method.set_weight) the number is counted in the unit of the weight instead, like bytes, since that is how far the progress bar advances.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..1_000_000, 1_000_000);
+    /// p.set_min_iterations(1000);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_min_iterations(&mut self, min_iterations: usize) {
+        self.bar.set_min_iterations(min_iterations as u64);
+    }
+
+    /// Same as [set_min_iterations()](struct.Prgrs.html#method.set_min_iterations), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..1_000_000, 1_000_000).set_min_iterations_move(1000){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_min_iterations_move(mut self, min_iterations: usize) -> Self {
+        self.bar.set_min_iterations(min_iterations as u64);
+        self
+    }
+
+    /// Set the [style](struct.Style.html) of the progress bar. The default is `Style::classic()`
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Style};
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_style(Style::arrow());
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_style(&mut self, style: Style) {
+        self.bar.set_style(style);
+    }
+
+    /// Same as [set_style()](struct.Prgrs.html#method.set_style), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Style};
+    /// for _ in Prgrs::new(0..100, 100).set_style_move(Style::blocks()){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_style_move(mut self, style: Style) -> Self {
+        self.bar.set_style(style);
+        self
+    }
+
+    /// Set whether the progress bar moves smoothly. The default is `false`
+    ///
+    /// A smooth bar is filled with unicode blocks, that can fill an eighth of a character, so even narrow bars move steadily.
+    /// The brackets and the empty part are taken from the [style](struct.Prgrs.html#method.set_style).
+    ///
+    /// If the output is a terminal, that doesn't use UTF-8 according to the locale, the bar is drawn like a normal one.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_smooth(true);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_smooth(&mut self, smooth: bool) {
+        self.bar.set_smooth(smooth);
+    }
+
+    /// Same as [set_smooth()](struct.Prgrs.html#method.set_smooth), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_smooth_move(true){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_smooth_move(mut self, smooth: bool) -> Self {
+        self.bar.set_smooth(smooth);
+        self
+    }
+
+    /// Set the [colors](struct.Colors.html) of the progress bar. The default is `Colors::none()`
+    ///
+    /// Colors are only used, when the output is a terminal and the `NO_COLOR` environment variable isn't set.
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Colors};
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_colors(Colors::states());
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_colors(&mut self, colors: Colors) {
+        self.bar.set_colors(colors);
+    }
+
+    /// Same as [set_colors()](struct.Prgrs.html#method.set_colors), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Color, Colors};
+    /// let colors = Colors {
+    ///     fill: Some(Color::Cyan),
+    ///     ..Colors::states()
+    /// };
+    /// for _ in Prgrs::new(0..100, 100).set_colors_move(colors){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_colors_move(mut self, colors: Colors) -> Self {
+        self.bar.set_colors(colors);
+        self
+    }
+
+    /// Set a [template](struct.Template.html), that defines the layout of the whole progress line.
+    ///
+    /// The [indicator](struct.Prgrs.html#method.set_indicator) and the settings, which information is shown after the bar, are ignored, as long as a template is set.
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Template};
+    /// let template = Template::new("{percent:>3}% {bar} {pos}/{len} [{elapsed}<{eta}, {rate}]").unwrap();
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_template(template);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_template(&mut self, template: Template) {
+        self.bar.set_template(template);
+    }
+
+    /// Same as [set_template()](struct.Prgrs.html#method.set_template), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Template};
+    /// # fn main() -> Result<(), prgrs::TemplateError> {
+    /// for _ in Prgrs::new(0..100, 100).set_template_move("{bar} {pos:>3}/{len}".parse()?){
+    ///     // do something here
+    ///}
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_template_move(mut self, template: Template) -> Self {
+        self.bar.set_template(template);
+        self
+    }
+
+    /// Set the description shown in front of the progress bar, for example the name of the file being processed.
+    ///
+    /// To change it while iterating, use the [labels()](struct.Prgrs.html#method.labels) handle.
+    /// A description, that is too long to leave room for the bar, is cut off.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_desc("Processing");
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_desc(&mut self, desc: &str) {
+        self.bar.set_desc(desc);
+    }
+
+    /// Same as [set_desc()](struct.Prgrs.html#method.set_desc), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_desc_move("Processing"){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_desc_move(mut self, desc: &str) -> Self {
+        self.bar.set_desc(desc);
+        self
+    }
+
+    /// Set the postfix shown at the end of the progress line, for example some intermediate result.
+    ///
+    /// To change it while iterating, use the [labels()](struct.Prgrs.html#method.labels) handle.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_postfix("loss=?");
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_postfix(&mut self, postfix: &str) {
+        self.bar.set_postfix(postfix);
+    }
+
+    /// Same as [set_postfix()](struct.Prgrs.html#method.set_postfix), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_postfix_move("loss=?"){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_postfix_move(mut self, postfix: &str) -> Self {
+        self.bar.set_postfix(postfix);
+        self
+    }
+
+    /// Returns a [handle](struct.Labels.html) to change the description and the postfix, while the progress bar is iterated over in a loop.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let files = vec!["a.txt", "b.txt", "c.txt"];
+    /// let p = Prgrs::new(files.iter(), files.len());
+    /// let labels = p.labels();
+    /// for file in p {
+    ///     labels.set_desc(file);
+    /// }
+    /// ```
+    pub fn labels(&self) -> Labels {
+        self.bar.labels()
+    }
+
+    /// Set the [indicator](enum.Indicator.html), that shows how far the progress bar is. The default is `Indicator::Percentage`
+    ///
+    /// When the number of elements is unknown, only the number of elements processed so far is shown.
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Indicator};
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_indicator(Indicator::Counter);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_indicator(&mut self, indicator: Indicator) {
+        self.bar.set_indicator(indicator);
+    }
+
+    /// Same as [set_indicator()](struct.Prgrs.html#method.set_indicator), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Indicator};
+    /// for _ in Prgrs::new(0..100, 100).set_indicator_move(Indicator::Both){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_indicator_move(mut self, indicator: Indicator) -> Self {
+        self.bar.set_indicator(indicator);
+        self
+    }
+
+    /// Set whether the time elapsed since the first iteration is shown. The default is `true`
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_show_elapsed(false);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_show_elapsed(&mut self, show: bool) {
+        self.bar.set_show_elapsed(show);
+    }
+
+    /// Same as [set_show_elapsed()](struct.Prgrs.html#method.set_show_elapsed), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_show_elapsed_move(false){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_show_elapsed_move(mut self, show: bool) -> Self {
+        self.bar.set_show_elapsed(show);
+        self
+    }
+
+    /// Set whether the estimated remaining time is shown. The default is `true`
+    ///
+    /// The estimate is only shown, when the number of elements is known.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_show_eta(false);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_show_eta(&mut self, show: bool) {
+        self.bar.set_show_eta(show);
+    }
+
+    /// Same as [set_show_eta()](struct.Prgrs.html#method.set_show_eta), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_show_eta_move(false){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_show_eta_move(mut self, show: bool) -> Self {
+        self.bar.set_show_eta(show);
+        self
+    }
+
+    /// Set whether the rate in iterations per second is shown. The default is `true`
+    ///
+    /// The rate is an exponential moving average, so it stays stable even if some elements take longer than others.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_show_rate(false);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_show_rate(&mut self, show: bool) {
+        self.bar.set_show_rate(show);
+    }
+
+    /// Same as [set_show_rate()](struct.Prgrs.html#method.set_show_rate), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_show_rate_move(false){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_show_rate_move(mut self, show: bool) -> Self {
+        self.bar.set_show_rate(show);
+        self
+    }
+
+    /// Set whether the progress bar stays visible once the Iterator has ended. The default is `true`
+    ///
+    /// Otherwise the line is cleared, or the bar is removed from its [MultiBar](struct.MultiBar.html).
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_leave(false);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_leave(&mut self, leave: bool) {
+        self.bar.set_leave(leave);
+    }
+
+    /// Same as [set_leave()](struct.Prgrs.html#method.set_leave), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// for _ in Prgrs::new(0..100, 100).set_leave_move(false){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_leave_move(mut self, leave: bool) -> Self {
+        self.bar.set_leave(leave);
+        self
+    }
+
+    /// Set how the progress is shown, when the output isn't a terminal. The default is `NonTerminal::Percentage(10.)`
+    ///
+    /// See [NonTerminal](enum.NonTerminal.html) for the options.
+    /// # Example
+    /// ```
+    /// use prgrs::{NonTerminal, Prgrs};
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_non_terminal(NonTerminal::Silent);
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_non_terminal(&mut self, non_terminal: NonTerminal) {
+        self.bar.set_non_terminal(non_terminal);
+    }
+
+    /// Same as [set_non_terminal()](struct.Prgrs.html#method.set_non_terminal), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{NonTerminal, Prgrs};
+    /// for _ in Prgrs::new(0..100, 100).set_non_terminal_move(NonTerminal::Percentage(25.)){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_non_terminal_move(mut self, non_terminal: NonTerminal) -> Self {
+        self.bar.set_non_terminal(non_terminal);
+        self
+    }
+
+    /// Set the [unit](enum.Unit.html), in which the counter and the rate are shown. The default is `Unit::Items`
+    ///
+    /// This is especially useful together with a [weight](struct.Prgrs.html#method.set_weight), for example to show the progress in bytes.
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Unit};
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// p.set_unit(Unit::Custom(String::from("files")));
+    /// for _ in p{
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_unit(&mut self, unit: Unit) {
+        self.bar.set_unit(unit);
+    }
+
+    /// Same as [set_unit()](struct.Prgrs.html#method.set_unit), but the Instance of Prgrs, on which it is called is moved out and returned afterwards, which is useful for a oneliner
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Unit};
+    /// for _ in Prgrs::new(0..100, 100).set_unit_move(Unit::Scaled(String::from("rows"))){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn set_unit_move(mut self, unit: Unit) -> Self {
+        self.bar.set_unit(unit);
+        self
+    }
+
+    /// Use this method to write to the [output](struct.Prgrs.html#method.set_output) of the progress bar, while displaying it.
+    ///
+    /// The progress bar is cleared, the text is printed and the progress bar is drawn below it again.
+    /// The text can have multiple lines.
+    /// # Example
+    /// ```
+    /// use prgrs::Prgrs;
+    /// let mut p = Prgrs::new(0..100, 100);
+    /// while let Some(i) = p.next() {
+    ///     if i % 10 == 0 {
+    ///         p.writeln(&format!("{}", i)).ok();
+    ///     }
+    /// }
+    /// ```
+    pub fn writeln(&mut self, text: &str) -> Result<(), Error> {
+        self.bar.writeln(text)
     }
 }
 
@@ -162,68 +781,263 @@ impl<T: Iterator> Iterator for Prgrs<T> {
     type Item = T::Item;
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.bar.step();
         let next = self.iter.next();
-        let mut percentage = self.get_ratio() * 100.;
-        if percentage > 100. || percentage.is_nan() {
-            percentage = 100.;
-        }
-        if let Some((Width(w), Height(_h))) = terminal_size() {
-            let whitespaces = std::iter::repeat(" ").take(w as usize).collect::<String>();
-            print!(
-                "\r{}\r{} ({:3.0}%)\r",
-                whitespaces,
-                self.create_bar(),
-                percentage
-            );
-        } else {
-            print!("{} ({:3.0}%)\r", self.create_bar(), percentage);
-        }
-        io::stdout().flush().ok();
-
-        if let None = next {
-            println!("");
+        match (&next, &self.weight) {
+            (Some(item), Some(weight)) => {
+                let n = weight(item);
+                self.bar.update();
+                self.bar.advance(n);
+            }
+            (Some(_), None) => {
+                self.bar.update();
+                self.bar.advance(1);
+            }
+            (None, _) => self.bar.finish(),
         }
-        self.curr += 1;
         next
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
 }
 
-/// Use this function to write to the terminal, while displaying a progress bar.
+/// Locks the mutex, even if another thread panicked while holding the lock.
 ///
-/// It may return an error, when the size of the terminal couldn't be determined.
+/// All data behind the mutexes of this crate is only used for drawing and always left in a valid state, so a panic somewhere else shouldn't stop the progress bars.
+pub(crate) fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the size from a `size_hint()`, if it is exact, since a wrong total would end the progress bar early or overflow it
+pub(crate) fn exact_size((lower, upper): (usize, Option<usize>)) -> Option<usize> {
+    upper.filter(|upper| *upper == lower)
+}
+
+/// An extension trait to wrap any Iterator in a progress bar.
 ///
-///In this case the supplied text will **NOT** be printed, so you may want to print it with `println!()` instead.
+/// The number of elements is taken from the Iterator, see [Prgrs::from_size_hint()](struct.Prgrs.html#method.from_size_hint).
+pub trait PrgrsExt: Iterator + Sized {
+    /// Wraps the Iterator in a progress bar
+    /// # Example
+    /// ```
+    /// use prgrs::PrgrsExt;
+    /// for _ in (0..100).prgrs(){
+    ///     // do something here
+    ///}
+    /// ```
+    fn prgrs(self) -> Prgrs<Self> {
+        Prgrs::from_size_hint(self)
+    }
+}
+
+impl<T: Iterator> PrgrsExt for T {}
+
+/// Use this function to write a line to stderr, while displaying progress bars.
+///
+/// The progress bars on stderr and stdout are cleared before the text is written and drawn again below it, see [suspend()](fn.suspend.html).
+/// The text can have multiple lines.
+///
+/// To write to the [output](enum.Output.html) of a single progress bar instead, use [Prgrs::writeln()](struct.Prgrs.html#method.writeln).
 /// # Example
 /// ```
 /// use prgrs::{Prgrs, writeln};
 /// for i in Prgrs::new(0..100, 100){
-///     if let Err(_) = writeln("test") {
-///         println!("test")
+///     if i % 10 == 0 {
+///         writeln("test").ok();
 ///     }
-/// }
+///}
 /// ```
 pub fn writeln(text: &str) -> Result<(), Error> {
-    if let Some((Width(w), Height(_h))) = terminal_size() {
-        // The whitespaces override the rest of the line, because \r doesn't delete characters already printed
-        let whitespaces = (w as usize).checked_sub(text.len()).unwrap_or(0);
-        let whitespaces = std::iter::repeat(" ").take(whitespaces).collect::<String>();
-        println!("\r{}{}", text, whitespaces);
-        Ok(())
-    } else {
-        Err(Error::new(
-            ErrorKind::Other,
-            "Issue determining size of your terminal",
-        ))
-    }
+    suspend(|| {
+        let mut stderr = io::stderr();
+        writeln!(stderr, "{}", text)?;
+        stderr.flush()
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    pub(crate) struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Buffer {
+        pub(crate) fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Sets up a progress bar, that draws every step without any times to the buffer, so the output can be compared
+    pub(crate) fn plain(bar: Bar, buf: &Buffer) -> Bar {
+        bar.set_length_move(Length::Absolute(13))
+            .set_min_interval_move(Duration::from_secs(0))
+            .set_show_elapsed_move(false)
+            .set_show_eta_move(false)
+            .set_show_rate_move(false)
+            .set_non_terminal_move(NonTerminal::Draw)
+            .set_output_move(Output::writer(buf.clone()))
+    }
+
+    fn plain_prgrs<T: Iterator>(mut p: Prgrs<T>, buf: &Buffer) -> Prgrs<T> {
+        let bar = std::mem::replace(&mut p.bar, Bar::with_total(None));
+        p.bar = plain(bar, buf);
+        p
+    }
+
     #[test]
     fn test_prgrs() {
         assert_eq!(Prgrs::new(1..100, 100).next(), (1..100).next());
         assert_eq!(Prgrs::new(1..100, 100).last(), (1..100).last());
         assert_eq!(Prgrs::new(0..0, 0).next(), None);
     }
+
+    #[test]
+    fn test_from_size_hint() {
+        assert_eq!(Prgrs::from_size_hint(0..42).bar.total(), Some(42));
+        assert_eq!([1, 2, 3].iter().prgrs().bar.total(), Some(3));
+        assert_eq!((0..).prgrs().bar.total(), None);
+        assert_eq!((0..10).filter(|i| i % 2 == 0).prgrs().bar.total(), None);
+        assert_eq!("üü".chars().prgrs().bar.total(), None);
+    }
+
+    #[test]
+    fn test_unknown_size() {
+        let buf = Buffer::default();
+        let mut p = Prgrs::from_size_hint((0..).take_while(|i| *i < 3))
+            .set_length_move(Length::Absolute(40))
+            .set_min_interval_move(Duration::from_secs(0))
+            .set_non_terminal_move(NonTerminal::Draw)
+            .set_output_move(Output::writer(buf.clone()));
+        p.by_ref().take(3).for_each(drop);
+        assert!(buf.contents().starts_with("\r[###     "));
+        assert!(buf.contents().contains("] 2it [00:00, "));
+        p.set_size(3);
+        p.next();
+        assert!(buf.contents().contains("] (100%) [00:00<"));
+        assert!(buf.contents().ends_with('\n'));
+        assert!(buf.contents().trim_end().ends_with("it/s]"));
+    }
+
+    #[test]
+    fn test_output() {
+        let buf = Buffer::default();
+        let p = plain_prgrs(Prgrs::new(0..2, 2), &buf);
+        assert_eq!(p.count(), 2);
+        assert_eq!(
+            buf.contents(),
+            "\r[    ] (  0%)\r[##  ] ( 50%)\r[####] (100%)\n"
+        );
+    }
+
+    #[test]
+    fn test_writeln() {
+        let buf = Buffer::default();
+        let mut p = plain_prgrs(Prgrs::new(0..2, 2), &buf);
+        p.next();
+        p.writeln("test").unwrap();
+        assert!(buf
+            .contents()
+            .ends_with("\r[    ] (  0%)\r             \rtest\n\r[##  ] ( 50%)"));
+        p.writeln("a\nb").unwrap();
+        assert!(buf
+            .contents()
+            .ends_with("\r             \ra\nb\n\r[##  ] ( 50%)"));
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let buf = Buffer::default();
+        let p = plain_prgrs(Prgrs::new(0..100, 100), &buf)
+            .set_min_interval_move(Duration::from_secs(3600));
+        assert_eq!(p.count(), 100);
+        assert_eq!(buf.contents(), "\r[    ] (  0%)\r[####] (100%)\n");
+
+        let buf = Buffer::default();
+        let p = plain_prgrs(Prgrs::new(0..100, 100), &buf).set_min_iterations_move(50);
+        assert_eq!(p.count(), 100);
+        assert_eq!(
+            buf.contents(),
+            "\r[    ] (  0%)\r[##  ] ( 50%)\r[####] (100%)\n"
+        );
+    }
+
+    #[test]
+    fn test_abandoned() {
+        let buf = Buffer::default();
+        let mut p = plain_prgrs(Prgrs::new(0..2, 2), &buf);
+        p.next();
+        drop(p);
+        assert_eq!(buf.contents(), "\r[    ] (  0%)\r[##  ] ( 50%)\n");
+    }
+
+    #[test]
+    fn test_template() {
+        let buf = Buffer::default();
+        let template = Template::new("{percent:>3}% {bar} {pos:>2}/{len}").unwrap();
+        let p = plain_prgrs(Prgrs::new(0..10, 10), &buf)
+            .set_length_move(Length::Absolute(20))
+            .set_min_iterations_move(5)
+            .set_template_move(template);
+        assert_eq!(p.count(), 10);
+        assert_eq!(
+            buf.contents(),
+            "\r  0% [       ]  0/10\r 50% [###    ]  5/10\r100% [#######] 10/10\n"
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        let buf = Buffer::default();
+        let p = plain_prgrs(Prgrs::new(0..2, 2), &buf)
+            .set_length_move(Length::Absolute(20))
+            .set_desc_move("a");
+        let labels = p.labels();
+        for i in p {
+            labels.set_postfix(&format!("i={}", i));
+        }
+        assert_eq!(
+            buf.contents(),
+            "\ra: [        ] (  0%)\ra: [##  ] ( 50%) i=0\ra: [####] (100%) i=1\n"
+        );
+    }
+
+    #[test]
+    fn test_indicator() {
+        let buf = Buffer::default();
+        let p = plain_prgrs(Prgrs::new(0..10, 10), &buf)
+            .set_length_move(Length::Absolute(14))
+            .set_min_iterations_move(5)
+            .set_indicator_move(Indicator::Counter);
+        assert_eq!(p.count(), 10);
+        assert_eq!(
+            buf.contents(),
+            "\r[    ] ( 0/10)\r[##  ] ( 5/10)\r[####] (10/10)\n"
+        );
+    }
+
+    #[test]
+    fn test_weight() {
+        let buf = Buffer::default();
+        let p = plain_prgrs(Prgrs::new([3, 0, 1].iter(), 4), &buf).set_weight_move(|i| **i);
+        assert_eq!(p.count(), 3);
+        assert_eq!(
+            buf.contents(),
+            "\r[    ] (  0%)\r[### ] ( 75%)\r[####] (100%)\n"
+        );
+    }
 }
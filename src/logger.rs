@@ -0,0 +1,142 @@
+use crate::screen::suspend;
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Wraps any logger, so its messages are printed above the progress bars instead of through them, this struct is available with the `log` feature.
+///
+/// The progress bars on stderr and stdout are [suspended](fn.suspend.html) for every message, that the inner logger accepts, and drawn again afterwards.
+/// # Example
+/// ```
+/// use log::{LevelFilter, Log, Metadata, Record};
+/// use prgrs::{LogWrapper, Prgrs};
+///
+/// struct StderrLogger;
+///
+/// impl Log for StderrLogger {
+///     fn enabled(&self, _: &Metadata) -> bool {
+///         true
+///     }
+///
+///     fn log(&self, record: &Record) {
+///         eprintln!("{} {}", record.level(), record.args());
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// LogWrapper::new(StderrLogger).try_init(LevelFilter::Info).unwrap();
+/// for i in Prgrs::new(0..100, 100){
+///     if i % 10 == 0 {
+///         log::info!("{} is divisible by 10", i);
+///     }
+///}
+/// ```
+pub struct LogWrapper<L> {
+    inner: L,
+}
+
+impl<L: Log + 'static> LogWrapper<L> {
+    /// Wraps the logger
+    pub fn new(inner: L) -> Self {
+        LogWrapper { inner }
+    }
+
+    /// Sets the wrapped logger as the global logger and sets the maximum level of the log crate
+    ///
+    /// Returns an error, if a global logger was already set.
+    pub fn try_init(self, max_level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl<L: Log> Log for LogWrapper<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        // Messages, that the inner logger ignores, don't need to clear the progress bars
+        if self.inner.enabled(record.metadata()) {
+            suspend(|| {
+                self.inner.log(record);
+                self.inner.flush();
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen::{self, suspend_on};
+    use crate::tests::Buffer;
+    use log::Level;
+    use std::io::Write;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Messages(Mutex<Vec<String>>);
+
+    impl Log for Messages {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Writes the messages to the buffer and marks those, that aren't written while the bars are suspended
+    struct Lines(Buffer);
+
+    impl Log for Lines {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            let mark = if screen::is_suspended() {
+                ""
+            } else {
+                "not suspended: "
+            };
+            writeln!(self.0.clone(), "{}{}", mark, record.args()).unwrap();
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_log() {
+        let logger = LogWrapper::new(Messages::default());
+        for (level, text) in [(Level::Info, "info"), (Level::Debug, "debug")] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .args(format_args!("{}", text))
+                    .build(),
+            );
+        }
+        assert!(!logger.enabled(&Metadata::builder().level(Level::Debug).build()));
+        assert_eq!(*logger.inner.0.lock().unwrap(), ["info"]);
+    }
+
+    #[test]
+    fn test_suspend() {
+        let buf = Buffer::default();
+        let logger = LogWrapper::new(Lines(buf.clone()));
+        let lines = [String::from("[##  ] ( 50%)")];
+        suspend_on(&mut buf.clone(), &lines, || {
+            logger.log(&Record::builder().args(format_args!("message")).build())
+        });
+        assert_eq!(buf.contents(), "\r             \rmessage\n\r[##  ] ( 50%)");
+    }
+}
@@ -0,0 +1,308 @@
+use crate::bar::Bar;
+use crate::output::Output;
+use crate::screen;
+use crate::{lock_unpoisoned, Prgrs};
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Draws several progress bars at once, each on its own line.
+///
+/// The MultiBar owns the output, so the bars added to it are drawn to the [output of the MultiBar](struct.MultiBar.html#method.set_output) instead of their own.
+/// Every time one of them is redrawn, the cursor is moved up and all lines are drawn again, so the bars don't overwrite each other.
+///
+/// Bars can be added at any time, also while the others are running. A bar is removed again, when it ends and [shouldn't be left](struct.Bar.html#method.set_leave) on the screen.
+/// Once all bars have ended, the cursor is moved below them, so the next bars are drawn underneath.
+///
+/// The handle can be cloned and sent to other threads.
+/// # Example
+/// ```
+/// use prgrs::{MultiBar, Prgrs};
+/// use std::thread;
+/// let multi = MultiBar::new();
+/// let workers: Vec<_> = (0..3)
+///     .map(|i| {
+///         let p = multi.add_prgrs(Prgrs::new(0..100, 100).set_desc_move(&format!("worker {}", i)));
+///         thread::spawn(move || {
+///             for _ in p{
+///                 // do something here
+///             }
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MultiBar {
+    inner: Arc<Mutex<Lines>>,
+}
+
+#[derive(Default)]
+struct Lines {
+    output: Output,
+    lines: Vec<Line>,
+    next_id: usize,
+    /// The number of lines, that are on the screen, the cursor is on the last of them
+    drawn: usize,
+}
+
+struct Line {
+    id: usize,
+    frame: String,
+    ended: bool,
+}
+
+/// The line of a bar inside a MultiBar
+pub(crate) struct Slot {
+    inner: Arc<Mutex<Lines>>,
+    id: usize,
+}
+
+impl MultiBar {
+    /// Creates an empty MultiBar, that draws to stderr
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output all bars of the MultiBar are drawn to, the default is `Output::Stderr`
+    /// # Example
+    /// ```
+    /// use prgrs::{MultiBar, Output};
+    /// let multi = MultiBar::new();
+    /// multi.set_output(Output::Stdout);
+    /// ```
+    pub fn set_output(&self, output: Output) {
+        self.lock().output = output;
+    }
+
+    /// Same as [set_output()](struct.MultiBar.html#method.set_output), but the MultiBar is moved out and returned afterwards, which is useful for a oneliner
+    pub fn set_output_move(self, output: Output) -> Self {
+        self.set_output(output);
+        self
+    }
+
+    /// Adds the bar in a new line below the other bars and returns it
+    /// # Example
+    /// ```
+    /// use prgrs::{Bar, MultiBar};
+    /// let multi = MultiBar::new();
+    /// let mut download = multi.add(Bar::new(1000).set_desc_move("download"));
+    /// let mut unpack = multi.add(Bar::new(10).set_desc_move("unpack"));
+    /// download.inc(1000);
+    /// download.finish();
+    /// unpack.inc(10);
+    /// unpack.finish();
+    /// ```
+    pub fn add(&self, mut bar: Bar) -> Bar {
+        let mut lines = self.lock();
+        let id = lines.next_id;
+        lines.next_id += 1;
+        lines.lines.push(Line {
+            id,
+            frame: String::new(),
+            ended: false,
+        });
+        bar.set_slot(Slot {
+            inner: self.inner.clone(),
+            id,
+        });
+        bar
+    }
+
+    /// Same as [add()](struct.MultiBar.html#method.add), but for the progress bar of an Iterator
+    pub fn add_prgrs<T: Iterator>(&self, mut p: Prgrs<T>) -> Prgrs<T> {
+        let bar = std::mem::replace(&mut p.bar, Bar::with_total(None));
+        p.bar = self.add(bar);
+        p
+    }
+
+    /// Writes the text above all bars, see [Prgrs::writeln()](struct.Prgrs.html#method.writeln)
+    pub fn writeln(&self, text: &str) -> Result<(), std::io::Error> {
+        self.lock().writeln(text)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lines> {
+        lock(&self.inner)
+    }
+}
+
+fn lock(inner: &Mutex<Lines>) -> MutexGuard<'_, Lines> {
+    lock_unpoisoned(inner)
+}
+
+impl Lines {
+    fn position(&self, id: usize) -> Option<usize> {
+        self.lines.iter().position(|line| line.id == id)
+    }
+
+    /// Draws all lines again, starting at the first one on the screen
+    fn redraw(&mut self) {
+        let mut buf = String::new();
+        self.render(&mut buf);
+        self.write(&buf).ok();
+    }
+
+    fn render(&mut self, buf: &mut String) {
+        if self.drawn > 1 {
+            buf.push_str(&format!("\x1b[{}A", self.drawn - 1));
+        }
+        buf.push('\r');
+        // Lines of removed bars, that are still on the screen, are cleared as well
+        let rows = self.lines.len().max(self.drawn);
+        for i in 0..rows {
+            if i > 0 {
+                buf.push('\n');
+            }
+            if let Some(line) = self.lines.get(i) {
+                buf.push_str(&line.frame);
+            }
+            buf.push_str("\x1b[K");
+        }
+        let cleared = rows.saturating_sub(self.lines.len().max(1));
+        if cleared > 0 {
+            buf.push_str(&format!("\x1b[{}A", cleared));
+        }
+        self.drawn = self.lines.len();
+    }
+
+    /// Writes the buffer, after which the lines are shown on the screen
+    fn write(&mut self, buf: &str) -> Result<(), std::io::Error> {
+        let shown: Vec<&str> = self.lines.iter().map(|line| line.frame.as_str()).collect();
+        screen::update(&mut self.output, &shown, |output| {
+            output.write_all(buf.as_bytes())?;
+            output.flush()
+        })
+    }
+
+    /// Moves the cursor below all lines, once every bar has ended
+    fn finish_if_ended(&mut self) {
+        if !self.lines.is_empty() && self.lines.iter().all(|line| line.ended) {
+            self.lines.clear();
+            self.drawn = 0;
+            self.write("\n").ok();
+        }
+    }
+
+    fn writeln(&mut self, text: &str) -> Result<(), std::io::Error> {
+        let mut buf = String::new();
+        if self.drawn > 1 {
+            buf.push_str(&format!("\x1b[{}A", self.drawn - 1));
+        }
+        // All lines are cleared first, so the text can have any number of lines
+        buf.push_str(&format!("\r\x1b[J{}\n", text));
+        self.drawn = 0;
+        self.render(&mut buf);
+        self.write(&buf)
+    }
+}
+
+impl Slot {
+    /// Calls the function with the output of the MultiBar
+    pub(crate) fn with_output<R, F: FnOnce(&Output) -> R>(&self, f: F) -> R {
+        f(&lock(&self.inner).output)
+    }
+
+    /// Replaces the line of the bar and draws all lines again
+    pub(crate) fn draw(&self, frame: &str) {
+        let mut lines = lock(&self.inner);
+        if let Some(i) = lines.position(self.id) {
+            lines.lines[i].frame = String::from(frame);
+            lines.redraw();
+        }
+    }
+
+    /// Marks the bar as ended, its line is removed, unless the bar should be left on the screen
+    pub(crate) fn end(&self, leave: bool) {
+        let mut lines = lock(&self.inner);
+        if let Some(i) = lines.position(self.id) {
+            if leave {
+                lines.lines[i].ended = true;
+            } else {
+                lines.lines.remove(i);
+                lines.redraw();
+            }
+            lines.finish_if_ended();
+        }
+    }
+
+    pub(crate) fn writeln(&self, text: &str) -> Result<(), std::io::Error> {
+        lock(&self.inner).writeln(text)
+    }
+
+    /// Writes a line of a bar, that isn't drawn, because the output isn't a terminal
+    pub(crate) fn write_log(&self, line: &str) -> Result<(), std::io::Error> {
+        let mut lines = lock(&self.inner);
+        screen::update(&mut lines.output, &[], |output| {
+            writeln!(output, "{}", line)?;
+            output.flush()
+        })
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        // A bar, that never started, doesn't leave an empty line behind
+        let mut lines = lock(&self.inner);
+        if let Some(i) = lines.position(self.id) {
+            if !lines.lines[i].ended {
+                lines.lines.remove(i);
+                if lines.drawn > 0 {
+                    lines.redraw();
+                }
+                lines.finish_if_ended();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{plain, Buffer};
+
+    #[test]
+    fn test_multi() {
+        let buf = Buffer::default();
+        let multi = MultiBar::new().set_output_move(Output::writer(buf.clone()));
+        let mut a = multi.add(plain(Bar::new(4), &buf));
+        let mut b = multi.add(plain(Bar::new(2), &buf));
+        a.inc(1);
+        b.inc(1);
+        a.inc(3);
+        a.finish();
+        b.inc(1);
+        b.finish();
+        assert_eq!(
+            buf.contents(),
+            concat!(
+                "\r[#   ] ( 25%)\x1b[K\n\x1b[K",
+                "\x1b[1A\r[#   ] ( 25%)\x1b[K\n[##  ] ( 50%)\x1b[K",
+                "\x1b[1A\r[####] (100%)\x1b[K\n[##  ] ( 50%)\x1b[K",
+                "\x1b[1A\r[####] (100%)\x1b[K\n[####] (100%)\x1b[K",
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let buf = Buffer::default();
+        let multi = MultiBar::new().set_output_move(Output::writer(buf.clone()));
+        let mut a = multi.add(plain(Bar::new(2), &buf).set_leave_move(false));
+        let b = multi.add(plain(Bar::new(2), &buf));
+        a.inc(1);
+        drop(b);
+        a.finish();
+        assert_eq!(
+            buf.contents(),
+            concat!(
+                "\r[##  ] ( 50%)\x1b[K\n\x1b[K",
+                "\x1b[1A\r[##  ] ( 50%)\x1b[K\n\x1b[K\x1b[1A",
+                "\r\x1b[K"
+            )
+        );
+        multi.writeln("text").unwrap();
+        assert!(buf.contents().ends_with("\r\x1b[Jtext\n\r"));
+    }
+}
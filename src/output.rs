@@ -0,0 +1,148 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+use terminal_size::Width;
+
+/// Use this enum to [set the output](struct.Prgrs.html#method.set_output) a progress bar is drawn to.
+///
+/// The default is `Output::Stderr`, so the bar doesn't get mixed up with the data your program writes to stdout.
+#[derive(Default)]
+pub enum Output {
+    /// Draw the progress bar to the standard error stream
+    #[default]
+    Stderr,
+    /// Draw the progress bar to the standard output stream
+    Stdout,
+    /// Draw the progress bar to any other writer, like a file or an in-memory buffer
+    ///
+    /// The size of the terminal can't be determined for these, so proportional lengths fall back to 50 characters.
+    /// They are never treated as terminals, see [NonTerminal](enum.NonTerminal.html).
+    Writer(Box<dyn Write + Send>),
+}
+
+/// Use this enum to [set how the progress is shown](struct.Prgrs.html#method.set_non_terminal), when the output isn't a terminal.
+///
+/// That is the case, when the output is redirected to a file or a pipe, like in the logs of a CI job, or when it is an `Output::Writer`.
+/// Redrawing the bar there would fill the log with a line for every frame, so instead a plain line like `42% 420/1000 elapsed 00:04 eta 00:05 98.76it/s` is printed from time to time.
+/// The last line is always printed, when the progress bar ends.
+/// # Example
+/// ```
+/// use prgrs::{NonTerminal, Prgrs};
+/// use std::time::Duration;
+/// for _ in Prgrs::new(0..100, 100).set_non_terminal_move(NonTerminal::Interval(Duration::from_secs(60))){
+///     // do something here
+///}
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NonTerminal {
+    /// Print a line each time the progress grows by the given percentage, this is the default with 10%
+    ///
+    /// While the total is unknown, a line is printed every 10 seconds instead.
+    Percentage(f64),
+    /// Print a line each time the given duration has passed
+    Interval(Duration),
+    /// Draw the progress bar just like on a terminal
+    Draw,
+    /// Don't print anything
+    Silent,
+}
+
+impl Default for NonTerminal {
+    fn default() -> Self {
+        NonTerminal::Percentage(10.)
+    }
+}
+
+impl Output {
+    /// Creates an `Output::Writer` from anything that implements `Write`
+    /// # Example
+    /// ```
+    /// use prgrs::{Prgrs, Output};
+    /// for _ in Prgrs::new(0..100, 100).set_output_move(Output::writer(std::io::sink())){
+    ///     // do something here
+    ///}
+    /// ```
+    pub fn writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Output::Writer(Box::new(writer))
+    }
+
+    /// Returns the width of the terminal behind this output, if there is one
+    pub(crate) fn terminal_width(&self) -> Option<usize> {
+        let size = match self {
+            Output::Stderr => stderr_size(),
+            Output::Stdout => terminal_size::terminal_size(),
+            Output::Writer(_) => None,
+        };
+        size.map(|(Width(w), _)| w as usize)
+    }
+
+    /// Returns whether this output is a terminal
+    pub(crate) fn is_terminal(&self) -> bool {
+        match self {
+            Output::Stderr => io::stderr().is_terminal(),
+            Output::Stdout => io::stdout().is_terminal(),
+            Output::Writer(_) => false,
+        }
+    }
+
+    /// Returns whether unicode characters can be drawn to this output
+    ///
+    /// Other writers are always written to in UTF-8, but for terminals this depends on the locale.
+    pub(crate) fn is_utf8(&self) -> bool {
+        match self {
+            Output::Stderr | Output::Stdout => locale_is_utf8(),
+            Output::Writer(_) => true,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn locale_is_utf8() -> bool {
+    // The first of these variables, that is set, determines the character encoding
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|val| !val.is_empty())
+        .unwrap_or_default()
+        .to_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+#[cfg(not(unix))]
+fn locale_is_utf8() -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn stderr_size() -> Option<(Width, terminal_size::Height)> {
+    use std::os::unix::io::AsRawFd;
+    terminal_size::terminal_size_using_fd(io::stderr().as_raw_fd())
+}
+
+#[cfg(windows)]
+fn stderr_size() -> Option<(Width, terminal_size::Height)> {
+    use std::os::windows::io::AsRawHandle;
+    terminal_size::terminal_size_using_handle(io::stderr().as_raw_handle())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn stderr_size() -> Option<(Width, terminal_size::Height)> {
+    None
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stderr => io::stderr().write(buf),
+            Output::Stdout => io::stdout().write(buf),
+            Output::Writer(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stderr => io::stderr().flush(),
+            Output::Stdout => io::stdout().flush(),
+            Output::Writer(w) => w.flush(),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::bar::Bar;
+use crate::shared::SharedBar;
+use rayon::iter::plumbing::{Consumer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+/// A progress bar for a rayon `ParallelIterator`, that is available with the `rayon` feature.
+///
+/// Every item processed by any of the threads advances a single progress bar, which is finished once the parallel iterator is done.
+/// Create it with [progress()](trait.ParallelPrgrsExt.html#method.progress).
+pub struct ParPrgrs<T> {
+    base: T,
+    bar: Bar,
+}
+
+/// Adds [progress()](trait.ParallelPrgrsExt.html#method.progress) to all parallel iterators, this trait is available with the `rayon` feature
+pub trait ParallelPrgrsExt: ParallelIterator {
+    /// Shows a progress bar, while the parallel iterator is processed.
+    ///
+    /// The total is the length of the iterator, if it is known up front, like for a `Vec` or a `Range`.
+    /// # Example
+    /// ```
+    /// use prgrs::ParallelPrgrsExt;
+    /// use rayon::prelude::*;
+    /// let squares: Vec<u64> = (0..1000u64).into_par_iter().progress().map(|i| i * i).collect();
+    /// ```
+    fn progress(self) -> ParPrgrs<Self> {
+        let total = self.opt_len().map(|len| len as u64);
+        self.progress_with(Bar::with_total(total))
+    }
+
+    /// Same as [progress()](trait.ParallelPrgrsExt.html#method.progress), but shows the given progress bar, which can be configured beforehand
+    /// # Example
+    /// ```
+    /// use prgrs::{Bar, ParallelPrgrsExt};
+    /// use rayon::prelude::*;
+    /// let files = vec!["a.txt", "b.txt", "c.txt"];
+    /// files
+    ///     .par_iter()
+    ///     .progress_with(Bar::new(3).set_desc_move("files"))
+    ///     .for_each(|file| {
+    ///         // do something here
+    ///     });
+    /// ```
+    fn progress_with(self, bar: Bar) -> ParPrgrs<Self> {
+        ParPrgrs { base: self, bar }
+    }
+}
+
+impl<T: ParallelIterator> ParallelPrgrsExt for T {}
+
+impl<T: ParallelIterator> ParallelIterator for ParPrgrs<T> {
+    type Item = T::Item;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let bar = SharedBar::from(self.bar);
+        let result = self
+            .base
+            .map(|item| {
+                bar.inc(1);
+                item
+            })
+            .drive_unindexed(consumer);
+        bar.finish();
+        result
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.base.opt_len()
+    }
+}
+
+impl<T: IndexedParallelIterator> IndexedParallelIterator for ParPrgrs<T> {
+    fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        let bar = SharedBar::from(self.bar);
+        let result = self
+            .base
+            .map(|item| {
+                bar.inc(1);
+                item
+            })
+            .drive(consumer);
+        bar.finish();
+        result
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        let bar = SharedBar::from(self.bar);
+        let result = self
+            .base
+            .map(|item| {
+                bar.inc(1);
+                item
+            })
+            .with_producer(callback);
+        bar.finish();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Output;
+    use crate::tests::{plain, Buffer};
+    use rayon::prelude::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_progress() {
+        let buf = Buffer::default();
+        let bar = plain(Bar::new(1000), &buf).set_min_interval_move(Duration::from_secs(3600));
+        let sum: u64 = (0..1000u64).into_par_iter().progress_with(bar).sum();
+        assert_eq!(sum, 499500);
+        assert!(buf.contents().ends_with("\r[####] (100%)\n"));
+        let squares: Vec<u64> = (0..4u64)
+            .into_par_iter()
+            .progress_with(Bar::new(4).set_output_move(Output::writer(std::io::sink())))
+            .map(|i| i * i)
+            .collect();
+        assert_eq!(squares, [0, 1, 4, 9]);
+    }
+}
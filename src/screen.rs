@@ -0,0 +1,159 @@
+use crate::format;
+use crate::lock_unpoisoned;
+use crate::output::Output;
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::sync::{Mutex, MutexGuard};
+
+/// The lines of progress bars, that are currently shown on stderr and stdout, the cursor is on the last of them
+struct Screen {
+    stderr: Vec<String>,
+    stdout: Vec<String>,
+}
+
+thread_local! {
+    /// Whether this thread is executing the closure passed to suspend()
+    static SUSPENDED: Cell<bool> = const { Cell::new(false) };
+}
+
+static SCREEN: Mutex<Screen> = Mutex::new(Screen {
+    stderr: Vec::new(),
+    stdout: Vec::new(),
+});
+
+fn lock() -> MutexGuard<'static, Screen> {
+    lock_unpoisoned(&SCREEN)
+}
+
+impl Screen {
+    fn lines(&mut self, output: &Output) -> Option<&mut Vec<String>> {
+        match output {
+            Output::Stderr => Some(&mut self.stderr),
+            Output::Stdout => Some(&mut self.stdout),
+            Output::Writer(_) => None,
+        }
+    }
+}
+
+/// Writes to the output and records the lines of progress bars, that are shown afterwards.
+///
+/// While writing to stderr or stdout, [suspend()](fn.suspend.html) has to wait, so it doesn't write in between.
+pub(crate) fn update<R, F: FnOnce(&mut Output) -> R>(
+    output: &mut Output,
+    lines: &[&str],
+    write: F,
+) -> R {
+    if let Output::Writer(_) = output {
+        return write(output);
+    }
+    let mut screen = lock();
+    let result = write(output);
+    if let Some(shown) = screen.lines(output) {
+        *shown = lines.iter().map(|line| String::from(*line)).collect();
+    }
+    result
+}
+
+/// Hides all progress bars on stderr and stdout, while the closure is executed, and draws them again afterwards.
+///
+/// Use it to print anything else, so it doesn't get mixed up with the progress bars, like the output of another library or a logger.
+/// While the closure is executed, all progress bars, that want to redraw, wait until it is done, so it must not use them itself.
+/// # Example
+/// ```
+/// use prgrs::{suspend, Prgrs};
+/// for i in Prgrs::new(0..100, 100){
+///     if i % 10 == 0 {
+///         suspend(|| println!("{} is divisible by 10", i));
+///     }
+///}
+/// ```
+pub fn suspend<R, F: FnOnce() -> R>(f: F) -> R {
+    // Calls from inside the closure, like from a logger, would wait for themselves
+    if SUSPENDED.with(Cell::get) {
+        return f();
+    }
+    let screen = lock();
+    let _suspension = Suspension::new();
+    suspend_on(&mut io::stderr(), &screen.stderr, || {
+        suspend_on(&mut io::stdout(), &screen.stdout, f)
+    })
+}
+
+/// Clears the lines from the output, while the closure is executed, and draws them again afterwards
+pub(crate) fn suspend_on<W: Write, R, F: FnOnce() -> R>(out: &mut W, lines: &[String], f: F) -> R {
+    clear(out, lines).ok();
+    let result = f();
+    restore(out, lines).ok();
+    result
+}
+
+/// Returns whether this thread is executing the closure passed to suspend()
+#[cfg(test)]
+pub(crate) fn is_suspended() -> bool {
+    SUSPENDED.with(Cell::get)
+}
+
+/// Marks this thread as suspended, until it is dropped, so the flag is also reset, if the closure panics
+struct Suspension;
+
+impl Suspension {
+    fn new() -> Self {
+        SUSPENDED.with(|suspended| suspended.set(true));
+        Suspension
+    }
+}
+
+impl Drop for Suspension {
+    fn drop(&mut self) {
+        SUSPENDED.with(|suspended| suspended.set(false));
+    }
+}
+
+/// Clears the lines, so the cursor is at the start of the first of them
+fn clear<W: Write>(out: &mut W, lines: &[String]) -> io::Result<()> {
+    match lines {
+        [] => return Ok(()),
+        // A single line is overwritten with whitespaces, which also works without ANSI escape sequences
+        [line] => write!(out, "\r{}\r", " ".repeat(format::visible_len(line)))?,
+        _ => write!(out, "\x1b[{}A\r\x1b[J", lines.len() - 1)?,
+    }
+    out.flush()
+}
+
+fn restore<W: Write>(out: &mut W, lines: &[String]) -> io::Result<()> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+    write!(out, "\r{}", lines.join("\n"))?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clear() {
+        let mut out = Vec::new();
+        let lines = [String::from("[##  ] ( 50%)")];
+        clear(&mut out, &lines).unwrap();
+        restore(&mut out, &lines).unwrap();
+        assert_eq!(out, b"\r             \r\r[##  ] ( 50%)");
+
+        let mut out = Vec::new();
+        let lines = [String::from("a"), String::from("b"), String::from("c")];
+        clear(&mut out, &lines).unwrap();
+        restore(&mut out, &lines).unwrap();
+        assert_eq!(out, b"\x1b[2A\r\x1b[J\ra\nb\nc");
+        clear(&mut out, &[]).unwrap();
+        restore(&mut out, &[]).unwrap();
+        assert_eq!(out, b"\x1b[2A\r\x1b[J\ra\nb\nc");
+    }
+
+    #[test]
+    fn test_suspend_panic() {
+        let result = std::panic::catch_unwind(|| suspend(|| panic!("logger failed")));
+        assert!(result.is_err());
+        assert!(!is_suspended());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::bar::Bar;
+use crate::labels::Labels;
+use crate::lock_unpoisoned;
+use std::io::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+
+/// A handle to a progress bar, that can be cloned and advanced from many threads at once.
+///
+/// The position is kept in an atomic counter, so advancing the bar never waits for another thread.
+/// The thread, that advanced it, also redraws it, unless another thread is drawing at the same time, in which case the redraw is left to the next advance.
+///
+/// The progress bar is drawn a last time, when it is [finished](struct.SharedBar.html#method.finish), or when the last handle is dropped.
+/// # Example
+/// ```
+/// use prgrs::{Bar, SharedBar};
+/// use std::thread;
+/// let bar = SharedBar::from(Bar::new(400).set_desc_move("workers"));
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             for _ in 0..100 {
+///                 // do something here
+///                 bar.inc(1);
+///             }
+///         });
+///     }
+/// });
+/// bar.finish();
+/// ```
+#[derive(Clone)]
+pub struct SharedBar {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    pos: AtomicU64,
+    bar: Mutex<Bar>,
+}
+
+impl SharedBar {
+    /// Creates a new shared progress bar with the given total
+    pub fn new(total: u64) -> Self {
+        Self::from(Bar::new(total))
+    }
+
+    /// Advances the progress bar by `n` and redraws it, if enough time has passed since the last redraw and no other thread is drawing it
+    pub fn inc(&self, n: u64) {
+        self.inner.pos.fetch_add(n, Ordering::Relaxed);
+        let mut bar = match self.inner.bar.try_lock() {
+            Ok(bar) => bar,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+        // The position is read after the lock is taken, so it never moves backwards
+        bar.set_position(self.position());
+    }
+
+    /// Returns the current position of the progress bar
+    pub fn position(&self) -> u64 {
+        self.inner.pos.load(Ordering::Relaxed)
+    }
+
+    /// Draws the progress bar a last time with the current position, see [Bar::finish()](struct.Bar.html#method.finish)
+    pub fn finish(&self) {
+        self.lock_synced().finish();
+    }
+
+    /// Same as [finish()](struct.SharedBar.html#method.finish), but the progress bar is marked as abandoned, see [Bar::abandon()](struct.Bar.html#method.abandon)
+    pub fn abandon(&self) {
+        self.lock_synced().abandon();
+    }
+
+    /// Redraws the progress bar without any progress, see [Bar::tick()](struct.Bar.html#method.tick)
+    pub fn tick(&self) {
+        self.lock_synced().tick();
+    }
+
+    /// Returns a [handle](struct.Labels.html) to change the description and the postfix
+    pub fn labels(&self) -> Labels {
+        self.lock().labels()
+    }
+
+    /// Use this method to write to the output of the progress bar, while displaying it, see [Prgrs::writeln()](struct.Prgrs.html#method.writeln)
+    pub fn writeln(&self, text: &str) -> Result<(), Error> {
+        self.lock().writeln(text)
+    }
+
+    /// Locks the bar and moves it to the current position without drawing it
+    fn lock_synced(&self) -> MutexGuard<'_, Bar> {
+        let mut bar = self.lock();
+        let behind = self.position().saturating_sub(bar.position());
+        bar.advance(behind);
+        bar
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Bar> {
+        lock_unpoisoned(&self.inner.bar)
+    }
+}
+
+impl From<Bar> for SharedBar {
+    /// Shares the bar with all its settings, it continues at its current position
+    fn from(bar: Bar) -> Self {
+        SharedBar {
+            inner: Arc::new(Shared {
+                pos: AtomicU64::new(bar.position()),
+                bar: Mutex::new(bar),
+            }),
+        }
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        // Draw the increments, that were left to another thread, before the bar is dropped and abandoned
+        let pos = *self.pos.get_mut();
+        let bar = self.bar.get_mut().unwrap_or_else(|e| e.into_inner());
+        let behind = pos.saturating_sub(bar.position());
+        bar.advance(behind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{plain, Buffer};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_shared() {
+        let buf = Buffer::default();
+        let bar = SharedBar::from(
+            plain(Bar::new(800), &buf).set_min_interval_move(Duration::from_secs(3600)),
+        );
+        thread::scope(|s| {
+            for _ in 0..8 {
+                let bar = bar.clone();
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        bar.inc(1);
+                    }
+                });
+            }
+        });
+        assert_eq!(bar.position(), 800);
+        bar.finish();
+        assert!(buf.contents().ends_with("\r[####] (100%)\n"));
+    }
+}
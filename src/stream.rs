@@ -0,0 +1,120 @@
+use crate::bar::Bar;
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Wraps a `Stream` in a progress bar, like [Prgrs](struct.Prgrs.html) does for an Iterator, this struct is available with the `futures` feature.
+    ///
+    /// The progress bar advances with every item the stream yields and is finished, once the stream ends.
+    /// The settings can be changed on the [progress bar](struct.Bar.html) itself, see [with_bar()](struct.PrgrsStream.html#method.with_bar) and [bar_mut()](struct.PrgrsStream.html#method.bar_mut).
+    /// # Example
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    /// use prgrs::PrgrsStream;
+    /// block_on(async {
+    ///     let mut s = PrgrsStream::new(stream::iter(0..100), 100);
+    ///     while let Some(_) = s.next().await {
+    ///         // do something here
+    ///     }
+    /// });
+    /// ```
+    pub struct PrgrsStream<S> {
+        #[pin]
+        stream: S,
+        bar: Bar,
+    }
+}
+
+impl<S: Stream> PrgrsStream<S> {
+    /// Creates a new PrgrsStream with the number of items the stream yields
+    pub fn new(stream: S, size: usize) -> Self {
+        Self::with_bar(stream, Bar::new(size as u64))
+    }
+
+    /// Creates a new PrgrsStream and takes the number of items from the `size_hint()` of the stream, if it is exact, see [Prgrs::from_size_hint()](struct.Prgrs.html#method.from_size_hint)
+    pub fn from_size_hint(stream: S) -> Self {
+        let size = crate::exact_size(stream.size_hint());
+        Self::with_bar(stream, Bar::with_total(size.map(|size| size as u64)))
+    }
+
+    /// Wraps the stream in the given progress bar, which can be configured beforehand
+    /// # Example
+    /// ```
+    /// use futures::stream;
+    /// use prgrs::{Bar, PrgrsStream};
+    /// let s = PrgrsStream::with_bar(stream::iter(0..100), Bar::new(100).set_desc_move("stream"));
+    /// ```
+    pub fn with_bar(stream: S, bar: Bar) -> Self {
+        PrgrsStream { stream, bar }
+    }
+}
+
+impl<S> PrgrsStream<S> {
+    /// Returns the progress bar, to change its settings
+    pub fn bar_mut(&mut self) -> &mut Bar {
+        &mut self.bar
+    }
+}
+
+impl<S: Stream> Stream for PrgrsStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let next = this.stream.poll_next(cx);
+        match &next {
+            Poll::Ready(Some(_)) => {
+                this.bar.step();
+                this.bar.update();
+                this.bar.advance(1);
+            }
+            Poll::Ready(None) => this.bar.finish(),
+            Poll::Pending => {}
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// An extension trait to wrap any `Stream` in a progress bar, this trait is available with the `futures` feature
+pub trait PrgrsStreamExt: Stream + Sized {
+    /// Wraps the stream in a progress bar, see [PrgrsStream::from_size_hint()](struct.PrgrsStream.html#method.from_size_hint)
+    /// # Example
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    /// use prgrs::PrgrsStreamExt;
+    /// let sum = block_on(stream::iter(0..100).prgrs().fold(0, |sum, i| async move { sum + i }));
+    /// ```
+    fn prgrs(self) -> PrgrsStream<Self> {
+        PrgrsStream::from_size_hint(self)
+    }
+}
+
+impl<S: Stream> PrgrsStreamExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{plain, Buffer};
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+
+    #[test]
+    fn test_stream() {
+        let buf = Buffer::default();
+        let bar = plain(Bar::new(4), &buf);
+        let items: Vec<i32> = block_on(PrgrsStream::with_bar(stream::iter(0..4), bar).collect());
+        assert_eq!(items, [0, 1, 2, 3]);
+        assert_eq!(
+            buf.contents(),
+            "\r[    ] (  0%)\r[#   ] ( 25%)\r[##  ] ( 50%)\r[### ] ( 75%)\r[####] (100%)\n"
+        );
+    }
+}
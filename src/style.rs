@@ -0,0 +1,86 @@
+use crate::format;
+
+/// Use this struct to [set the style](struct.Prgrs.html#method.set_style) of the progress bar.
+///
+/// There are a few presets, but you can also set every character yourself.
+/// # Example
+/// ```
+/// use prgrs::Style;
+/// let style = Style {
+///     fill: '=',
+///     empty: '-',
+///     head: Some('>'),
+///     left: String::from("|"),
+///     right: String::from("|"),
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    /// The character used for the finished part of the bar
+    pub fill: char,
+    /// The character used for the unfinished part of the bar
+    pub empty: char,
+    /// The character drawn at the end of the finished part, as long as the bar isn't full
+    pub head: Option<char>,
+    /// Drawn left of the bar, can be empty
+    pub left: String,
+    /// Drawn right of the bar, can be empty
+    pub right: String,
+}
+
+impl Style {
+    /// `[#######         ]`, this is the default
+    pub fn classic() -> Self {
+        Style {
+            fill: '#',
+            empty: ' ',
+            head: None,
+            left: String::from("["),
+            right: String::from("]"),
+        }
+    }
+
+    /// `[======>         ]`
+    pub fn arrow() -> Self {
+        Style {
+            fill: '=',
+            empty: ' ',
+            head: Some('>'),
+            left: String::from("["),
+            right: String::from("]"),
+        }
+    }
+
+    /// `|███████░░░░░░░░░|`
+    pub fn blocks() -> Self {
+        Style {
+            fill: '█',
+            empty: '░',
+            head: None,
+            left: String::from("|"),
+            right: String::from("|"),
+        }
+    }
+
+    /// `[●●●●●●●·········]`
+    pub fn dots() -> Self {
+        Style {
+            fill: '●',
+            empty: '·',
+            head: None,
+            left: String::from("["),
+            right: String::from("]"),
+        }
+    }
+
+    /// The number of columns used by the brackets
+    pub(crate) fn brackets_len(&self) -> usize {
+        format::visible_len(&self.left) + format::visible_len(&self.right)
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::classic()
+    }
+}
@@ -0,0 +1,314 @@
+use crate::format;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Use this struct to [set the template](struct.Prgrs.html#method.set_template), that defines the layout of the whole progress line.
+///
+/// A template is text with fields in curly braces, that are replaced by their values each time the progress bar is drawn.
+/// These fields are available:
+///
+/// | Field       | Value                                               |
+/// |-------------|-----------------------------------------------------|
+/// | `{bar}`     | The bar itself, which takes up the remaining length |
+/// | `{percent}` | The percentage without the `%` sign                 |
+/// | `{pos}`     | The number of elements processed so far             |
+/// | `{len}`     | The total number of elements                        |
+/// | `{elapsed}` | The time elapsed since the first iteration          |
+/// | `{eta}`     | The estimated remaining time                        |
+/// | `{rate}`    | The rate per second                                 |
+/// | `{desc}`    | The [description](struct.Prgrs.html#method.set_desc) |
+/// | `{postfix}` | The [postfix](struct.Prgrs.html#method.set_postfix) |
+///
+/// `{pos}`, `{len}` and `{rate}` are shown in the [unit](enum.Unit.html) of the progress bar.
+/// Values, that aren't known yet, are shown as `?`.
+///
+/// Like in `format!()` a width and an alignment can be specified after a colon, e.g. `{pos:>5}`, `{rate:<12}` or `{percent:^3}`.
+/// The width is measured in terminal columns, so wide characters and ANSI escape sequences in descriptions are padded correctly.
+/// Without an alignment the value is aligned to the right.
+/// For `{bar}` the width sets the length of the bar including the brackets instead of filling the remaining length.
+///
+/// Literal curly braces are written as `{{` and `}}`.
+/// # Example
+/// ```
+/// use prgrs::Template;
+/// let template = Template::new("{desc}: {percent:>3}% {bar} {pos}/{len} [{elapsed}<{eta}, {rate}]").unwrap();
+/// assert!(Template::new("{bar} {foo}").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Field(Field),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Field {
+    key: Key,
+    align: Align,
+    width: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Key {
+    Bar,
+    Percent,
+    Pos,
+    Len,
+    Elapsed,
+    Eta,
+    Rate,
+    Desc,
+    Postfix,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A part of a rendered template
+#[derive(Debug, PartialEq)]
+pub(crate) enum Rendered {
+    Text(String),
+    /// The bar, with the length set in the template, if there is one
+    Bar(Option<usize>),
+}
+
+impl Key {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bar" => Some(Key::Bar),
+            "percent" => Some(Key::Percent),
+            "pos" => Some(Key::Pos),
+            "len" => Some(Key::Len),
+            "elapsed" => Some(Key::Elapsed),
+            "eta" => Some(Key::Eta),
+            "rate" => Some(Key::Rate),
+            "desc" => Some(Key::Desc),
+            "postfix" => Some(Key::Postfix),
+            _ => None,
+        }
+    }
+}
+
+impl Template {
+    /// Parses a template, see [above](struct.Template.html) for the syntax.
+    ///
+    /// Returns an error describing the problem and its position, if the template is invalid.
+    pub fn new(template: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let end = template[i..]
+                        .find('}')
+                        .ok_or_else(|| TemplateError::new(i, "unclosed `{`"))?;
+                    let field = Field::parse(&template[i + 1..i + end], i)?;
+                    // Skip the rest of the field
+                    while chars.next_if(|&(j, _)| j <= i + end).is_some() {}
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => {
+                    return Err(TemplateError::new(
+                        i,
+                        "unmatched `}`, use `}}` for a literal one",
+                    ))
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Replaces all fields except for the bar with the values returned by the closure
+    pub(crate) fn render<F: Fn(Key) -> String>(&self, value: F) -> Vec<Rendered> {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => Rendered::Text(text.clone()),
+                Part::Field(Field {
+                    key: Key::Bar,
+                    width,
+                    ..
+                }) => Rendered::Bar(*width),
+                Part::Field(field) => Rendered::Text(field.pad(value(field.key))),
+            })
+            .collect()
+    }
+}
+
+impl Field {
+    /// Parses the inside of the curly braces, `pos` is the position of the opening brace in the template
+    fn parse(field: &str, pos: usize) -> Result<Self, TemplateError> {
+        let (name, spec) = match field.find(':') {
+            Some(colon) => (&field[..colon], Some(&field[colon + 1..])),
+            None => (field, None),
+        };
+        let name = name.trim();
+        let key = Key::from_name(name)
+            .ok_or_else(|| TemplateError::new(pos, &format!("unknown field `{}`", name)))?;
+        let mut align = Align::Right;
+        let mut width = None;
+        if let Some(spec) = spec {
+            let digits = match spec.chars().next() {
+                Some('<') => {
+                    align = Align::Left;
+                    &spec[1..]
+                }
+                Some('^') => {
+                    align = Align::Center;
+                    &spec[1..]
+                }
+                Some('>') => &spec[1..],
+                _ => spec,
+            };
+            width = Some(digits.parse().map_err(|_| {
+                TemplateError::new(
+                    pos,
+                    &format!("invalid width `{}` for field `{}`", digits, name),
+                )
+            })?);
+        }
+        Ok(Field { key, align, width })
+    }
+
+    fn pad(&self, value: String) -> String {
+        let width = match self.width {
+            Some(width) => width,
+            None => return value,
+        };
+        // Padded by hand, since format!() counts chars instead of columns
+        let fill = width.saturating_sub(format::visible_len(&value));
+        let (left, right) = match self.align {
+            Align::Left => (0, fill),
+            Align::Center => (fill / 2, fill - fill / 2),
+            Align::Right => (fill, 0),
+        };
+        format!("{}{}{}", " ".repeat(left), value, " ".repeat(right))
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::new(s)
+    }
+}
+
+/// The error returned, when a [Template](struct.Template.html) is invalid
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateError {
+    pos: usize,
+    message: String,
+}
+
+impl TemplateError {
+    fn new(pos: usize, message: &str) -> Self {
+        TemplateError {
+            pos,
+            message: String::from(message),
+        }
+    }
+
+    /// The position in the template, at which the problem was found
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid template at position {}: {}",
+            self.pos, self.message
+        )
+    }
+}
+
+impl Error for TemplateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str) -> Vec<Rendered> {
+        Template::new(template).unwrap().render(|key| match key {
+            Key::Percent => String::from("42"),
+            Key::Pos => String::from("420"),
+            Key::Len => String::from("1000"),
+            Key::Desc => String::from("下载"),
+            _ => String::from("?"),
+        })
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("{percent:>3}% {bar} {pos:<5}/{len}"),
+            vec![
+                Rendered::Text(String::from(" 42")),
+                Rendered::Text(String::from("% ")),
+                Rendered::Bar(None),
+                Rendered::Text(String::from(" ")),
+                Rendered::Text(String::from("420  ")),
+                Rendered::Text(String::from("/")),
+                Rendered::Text(String::from("1000")),
+            ]
+        );
+        assert_eq!(
+            render("{{{bar:20}}} {eta:^3}"),
+            vec![
+                Rendered::Text(String::from("{")),
+                Rendered::Bar(Some(20)),
+                Rendered::Text(String::from("} ")),
+                Rendered::Text(String::from(" ? ")),
+            ]
+        );
+        assert_eq!(
+            render("{desc:<6}|{desc:^7}"),
+            vec![
+                Rendered::Text(String::from("下载  ")),
+                Rendered::Text(String::from("|")),
+                Rendered::Text(String::from(" 下载  ")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = Template::new("{bar} {foo}").unwrap_err();
+        assert_eq!(err.position(), 6);
+        assert_eq!(
+            err.to_string(),
+            "invalid template at position 6: unknown field `foo`"
+        );
+        assert!(Template::new("{bar").is_err());
+        assert!(Template::new("bar}").is_err());
+        assert!(Template::new("{pos:>x}").is_err());
+    }
+}
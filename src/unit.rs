@@ -0,0 +1,148 @@
+/// Use this enum to [set the unit](struct.Prgrs.html#method.set_unit), in which the counter and the rate are shown.
+///
+/// | Unit              | Counter            | Rate           |
+/// |-------------------|--------------------|----------------|
+/// | `Items`           | `420/1000`         | `12.34it/s`    |
+/// | `Custom("files")` | `420/1000`         | `12.34files/s` |
+/// | `Scaled("rows")`  | `42.0k/1.00M`      | `1.23krows/s`  |
+/// | `Bytes`           | `12.3 MB/100 MB`   | `1.23 MB/s`    |
+/// | `BinaryBytes`     | `12.3 MiB/100 MiB` | `1.23 MiB/s`   |
+///
+/// While the total is unknown, the unit is also shown after the position, like `42it`.
+/// # Example
+/// ```
+/// use prgrs::{Prgrs, Unit};
+/// let chunks = vec![4096; 100];
+/// let p = Prgrs::new(chunks.into_iter(), 409600)
+///     .set_weight_move(|chunk| *chunk as u64)
+///     .set_unit_move(Unit::BinaryBytes);
+/// for _ in p{
+///     // process the chunk here
+///}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Unit {
+    /// Plain numbers of iterations, this is the default
+    #[default]
+    Items,
+    /// Plain numbers with a custom label
+    Custom(String),
+    /// Numbers scaled with `k`, `M`, `G`, ... and a custom label
+    Scaled(String),
+    /// Bytes with SI prefixes, which are powers of 1000
+    Bytes,
+    /// Bytes with binary prefixes, which are powers of 1024
+    BinaryBytes,
+}
+
+const SI: [&str; 7] = ["", "k", "M", "G", "T", "P", "E"];
+const BINARY: [&str; 7] = ["", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei"];
+
+impl Unit {
+    fn label(&self) -> &str {
+        match self {
+            Unit::Items => "it",
+            Unit::Custom(label) | Unit::Scaled(label) => label,
+            Unit::Bytes | Unit::BinaryBytes => "B",
+        }
+    }
+
+    /// Formats an amount like the position or the total, bytes include their unit
+    pub(crate) fn amount(&self, n: u64) -> String {
+        match self {
+            Unit::Items | Unit::Custom(_) => n.to_string(),
+            Unit::Scaled(_) => scale(n as f64, 1000., &SI, ""),
+            Unit::Bytes => scale(n as f64, 1000., &SI, " ") + "B",
+            Unit::BinaryBytes => scale(n as f64, 1024., &BINARY, " ") + "B",
+        }
+    }
+
+    /// Formats an amount followed by the unit, like `42it`
+    pub(crate) fn count(&self, n: u64) -> String {
+        match self {
+            Unit::Bytes | Unit::BinaryBytes => self.amount(n),
+            _ => self.amount(n) + self.label(),
+        }
+    }
+
+    /// Formats the rate per second, like `12.34it/s` or `1.23 MiB/s`
+    pub(crate) fn rate(&self, rate: Option<f64>) -> String {
+        let rate = match rate {
+            Some(r) if r.is_finite() => r,
+            _ => {
+                return match self {
+                    Unit::Bytes | Unit::BinaryBytes => String::from("? B/s"),
+                    _ => format!("?{}/s", self.label()),
+                }
+            }
+        };
+        match self {
+            Unit::Items | Unit::Custom(_) => format!("{:.2}{}/s", rate, self.label()),
+            Unit::Scaled(label) => format!("{}{}/s", scale(rate, 1000., &SI, ""), label),
+            Unit::Bytes => format!("{}B/s", scale(rate, 1000., &SI, " ")),
+            Unit::BinaryBytes => format!("{}B/s", scale(rate, 1024., &BINARY, " ")),
+        }
+    }
+}
+
+/// Divides the value by the base until it is smaller than the base and appends the prefix after the separator.
+///
+/// Whole numbers without a prefix are shown as they are, everything else with three significant digits.
+fn scale(mut value: f64, base: f64, prefixes: &[&str], sep: &str) -> String {
+    let mut i = 0;
+    while value >= base && i + 1 < prefixes.len() {
+        value /= base;
+        i += 1;
+    }
+    let number = if (i == 0 && value.fract() == 0.) || value >= 99.95 {
+        format!("{:.0}", value)
+    } else if value >= 9.995 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.2}", value)
+    };
+    format!("{}{}{}", number, sep, prefixes[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount() {
+        assert_eq!(Unit::Items.amount(1234567), "1234567");
+        assert_eq!(Unit::Scaled(String::from("rows")).amount(420), "420");
+        assert_eq!(Unit::Scaled(String::from("rows")).amount(42000), "42.0k");
+        assert_eq!(Unit::Bytes.amount(999), "999 B");
+        assert_eq!(Unit::Bytes.amount(12_345_678), "12.3 MB");
+        assert_eq!(Unit::BinaryBytes.amount(1024), "1.00 KiB");
+        assert_eq!(Unit::BinaryBytes.amount(100 << 20), "100 MiB");
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(Unit::Items.count(42), "42it");
+        assert_eq!(Unit::Custom(String::from(" files")).count(42), "42 files");
+        assert_eq!(Unit::Bytes.count(42), "42 B");
+    }
+
+    #[test]
+    fn test_rate() {
+        assert_eq!(Unit::Items.rate(Some(12.344)), "12.34it/s");
+        assert_eq!(Unit::Items.rate(None), "?it/s");
+        assert_eq!(
+            Unit::Custom(String::from("files")).rate(Some(1.5)),
+            "1.50files/s"
+        );
+        assert_eq!(
+            Unit::Scaled(String::from("rows")).rate(Some(1234.)),
+            "1.23krows/s"
+        );
+        assert_eq!(
+            Unit::BinaryBytes.rate(Some(12.3 * 1024. * 1024.)),
+            "12.3 MiB/s"
+        );
+        assert_eq!(Unit::Bytes.rate(Some(4.2)), "4.20 B/s");
+        assert_eq!(Unit::Bytes.rate(None), "? B/s");
+    }
+}